@@ -0,0 +1,58 @@
+use ::syn::{self, Field, VariantData, Ident, MetaItem, Lit};
+use ::quote::{self, Tokens};
+
+pub fn transcoder_decorator(ast: &syn::MacroInput) -> Result<quote::Tokens, &str> {
+    let struct_fields = match ast.body {
+        syn::Body::Struct(VariantData::Struct(ref data)) => data,
+        _ => return Err("Must decorate a struct"),
+    };
+
+    if ast.generics.lifetimes.len() > 0 || ast.generics.ty_params.len() > 0 {
+        return Err("Exception struct can not have lifetime or type arguments");
+    }
+
+    let module = match get_module(ast) {
+        Some(module) => module,
+        None => return Err("Exception struct must have a #[module = \"Elixir.Module\"] attribute"),
+    };
+
+    let encoder = gen_encoder(&ast.ident, struct_fields, &module);
+
+    Ok(quote! {
+        #encoder
+    })
+}
+
+fn get_module(ast: &syn::MacroInput) -> Option<String> {
+    ast.attrs.iter()
+        .filter_map(|attr| match attr.value {
+            MetaItem::NameValue(ref name, Lit::Str(ref value, _)) if name == "module" =>
+                Some(value.clone()),
+            _ => None,
+        })
+        .next()
+}
+
+pub fn gen_encoder(struct_name: &Ident, fields: &Vec<Field>, module: &str) -> Tokens {
+    let field_defs: Vec<Tokens> = fields.iter().map(|field| {
+        let field_ident = field.clone().ident.unwrap();
+        let field_ident_str = field_ident.to_string();
+        quote! {
+            (rustler_atom!(#field_ident_str).to_term(env), rustler::NifEncoder::encode(&self.#field_ident, env))
+        }
+    }).collect();
+
+    quote! {
+        impl rustler::NifEncoder for #struct_name {
+            fn encode<'a>(&self, env: rustler::NifEnv<'a>) -> rustler::NifTerm<'a> {
+                rustler::exception::make_ex_exception_with_fields(env, #module, &[#(#field_defs),*])
+            }
+        }
+
+        impl From<#struct_name> for rustler::NifError {
+            fn from(exception: #struct_name) -> rustler::NifError {
+                rustler::NifError::RaiseTerm(Box::new(exception))
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use ::syn::{self, Field, VariantData, Ident};
+use ::quote::{self, Tokens};
+
+pub fn transcoder_decorator(ast: &syn::MacroInput) -> Result<quote::Tokens, &str> {
+    let struct_fields = match ast.body {
+        syn::Body::Struct(VariantData::Struct(ref data)) => data,
+        _ => return Err("Must decorate a struct"),
+    };
+
+    let num_lifetimes = ast.generics.lifetimes.len();
+    if num_lifetimes > 1 {
+        return Err("Struct can only have one lifetime argument");
+    }
+    let has_lifetime = num_lifetimes == 1;
+
+    let decoder = gen_decoder(&ast.ident, struct_fields, has_lifetime);
+    let encoder = gen_encoder(&ast.ident, struct_fields, has_lifetime);
+
+    Ok(quote! {
+        #decoder
+        #encoder
+    })
+}
+
+/// The decoder starts from `Default::default()` and sets the fields whose keys are in the list,
+/// so options that are left out keep their default values. Keys that are not fields are ignored.
+pub fn gen_decoder(struct_name: &Ident, fields: &Vec<Field>, has_lifetime: bool) -> Tokens {
+    let field_defs: Vec<Tokens> = fields.iter().map(|field| {
+        let ident = field.clone().ident.unwrap();
+        let ident_str = ident.to_string();
+        quote! {
+            {
+                let value = try!(list.get_decoded(rustler_atom!(#ident_str)).map_err(|err| err.at_field(#ident_str)));
+                if let Some(value) = value {
+                    result.#ident = value;
+                }
+            }
+        }
+    }).collect();
+
+    let struct_type = if has_lifetime {
+        quote! { #struct_name <'a> }
+    } else {
+        quote! { #struct_name }
+    };
+
+    quote! {
+        impl<'a> rustler::NifDecoder<'a> for #struct_type {
+            fn decode(term: rustler::NifTerm<'a>) -> Result<Self, rustler::NifError> {
+                let list: rustler::types::keyword_list::NifKeywordList<'a> = try!(term.decode());
+                let mut result: Self = Default::default();
+                #(#field_defs)*
+                Ok(result)
+            }
+        }
+    }
+}
+
+pub fn gen_encoder(struct_name: &Ident, fields: &Vec<Field>, has_lifetime: bool) -> Tokens {
+    let field_defs: Vec<Tokens> = fields.iter().map(|field| {
+        let field_ident = field.clone().ident.unwrap();
+        let field_ident_str = field_ident.to_string();
+        quote! {
+            rustler::types::tuple::make_tuple(env, &[rustler_atom!(#field_ident_str).to_term(env), self.#field_ident.encode(env)])
+        }
+    }).collect();
+
+    let struct_type = if has_lifetime {
+        quote! { #struct_name <'b> }
+    } else {
+        quote! { #struct_name }
+    };
+
+    quote! {
+        impl<'b> rustler::NifEncoder for #struct_type {
+            fn encode<'a>(&self, env: rustler::NifEnv<'a>) -> rustler::NifTerm<'a> {
+                use rustler::NifEncoder;
+                let items: Vec<rustler::NifTerm<'a>> = vec![#(#field_defs),*];
+                items.encode(env)
+            }
+        }
+    }
+}
@@ -23,6 +23,8 @@ use std::path::Path;
 mod util;
 mod tuple;
 mod map;
+mod keyword_list;
+mod exception;
 
 #[cfg(feature = "with-syntex")]
 pub fn expand<S, D>(src: S, dst: D) -> Result<(), syntex::Error>
@@ -32,6 +34,9 @@ pub fn expand<S, D>(src: S, dst: D) -> Result<(), syntex::Error>
 
     reg.add_decorator("derive_NifMap", map_transcoder_decorator_shim);
     reg.add_decorator("derive_NifTuple", tuple_transcoder_decorator_shim);
+    reg.add_decorator("derive_NifKeywordList", keyword_list_transcoder_decorator_shim);
+    reg.add_decorator("derive_NifException", exception_transcoder_decorator_shim);
+    reg.add_post_expansion_pass(strip_attributes);
 
     //reg.add_decorator("NifResource", resource::resource_struct_def_decorator);
     //use syntax::ext::base::{TTMacroExpander, ExtCtxt, MacResult};
@@ -53,6 +58,30 @@ pub fn expand<S, D>(src: S, dst: D) -> Result<(), syntex::Error>
     reg.expand("", src.as_ref(), dst.as_ref())
 }
 
+/// Removes the `#[module = "..."]` attributes read by the decorators, since rustc does not know
+/// them.
+#[cfg(feature = "with-syntex")]
+fn strip_attributes(krate: syntax::ast::Crate) -> syntax::ast::Crate {
+    use syntax::{ast, fold};
+
+    struct StripAttributeFolder;
+
+    impl fold::Folder for StripAttributeFolder {
+        fn fold_attribute(&mut self, attr: ast::Attribute) -> Option<ast::Attribute> {
+            match attr.node.value.node {
+                ast::MetaItemKind::NameValue(ref name, _) if name == &"module" => None,
+                _ => Some(attr),
+            }
+        }
+
+        fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+            fold::noop_fold_mac(mac, self)
+        }
+    }
+
+    fold::Folder::fold_crate(&mut StripAttributeFolder, krate)
+}
+
 macro_rules! shim_syn_decorator {
     ($decorator_name:ident, $shim_name:ident, $wrapped:path) => {
         fn $shim_name(
@@ -95,6 +124,8 @@ macro_rules! shim_syn_decorator {
 
 shim_syn_decorator!(NifTuple, tuple_transcoder_decorator_shim, tuple::transcoder_decorator);
 shim_syn_decorator!(NifMap, map_transcoder_decorator_shim, map::transcoder_decorator);
+shim_syn_decorator!(NifKeywordList, keyword_list_transcoder_decorator_shim, keyword_list::transcoder_decorator);
+shim_syn_decorator!(NifException, exception_transcoder_decorator_shim, exception::transcoder_decorator);
 
 #[cfg(not(feature = "with-syntex"))]
 #[plugin_registrar]
@@ -105,6 +136,13 @@ pub fn register(reg: &mut rustc_plugin::Registry) {
     reg.register_syntax_extension(
         syntax::parse::token::intern("derive_NifTuple"),
         syntax::ext::base::MultiDecorator(Box::new(tuple_transcoder_decorator_shim)));
+    reg.register_syntax_extension(
+        syntax::parse::token::intern("derive_NifKeywordList"),
+        syntax::ext::base::MultiDecorator(Box::new(keyword_list_transcoder_decorator_shim)));
+    reg.register_syntax_extension(
+        syntax::parse::token::intern("derive_NifException"),
+        syntax::ext::base::MultiDecorator(Box::new(exception_transcoder_decorator_shim)));
+    reg.register_attribute("module".to_string(), syntax::feature_gate::AttributeType::Whitelisted);
 
     //reg.register_syntax_extension(
     //    builder.name("NifResource"),
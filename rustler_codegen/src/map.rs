@@ -28,17 +28,9 @@ pub fn gen_decoder(struct_name: &Ident, fields: &Vec<Field>, has_lifetime: bool)
         let ident_str = ident.to_string();
         quote! {
             #ident: {
-                match rustler::NifDecoder::decode(
-                    match rustler::map::get_map_value(
-                        term,
-                        rustler::atom::get_atom_init(#ident_str).to_term(env))
-                    {
-                        Some(term) => term,
-                        None => return Err(rustler::NifError::BadArg),
-                    })
-                {
-                    Ok(res) => res,
-                    Err(err) => return Err(err),
+                match term.map_get(rustler_atom!(#ident_str).to_term(env)) {
+                    Ok(value) => try!(value.decode().map_err(|err| err.at_field(#ident_str))),
+                    Err(_) => return Err(rustler::NifError::expected("key").at_field(#ident_str)),
                 }
             }
         }
@@ -54,6 +46,9 @@ pub fn gen_decoder(struct_name: &Ident, fields: &Vec<Field>, has_lifetime: bool)
         impl<'a> rustler::NifDecoder<'a> for #struct_type {
             fn decode(term: rustler::NifTerm<'a>) -> Result<Self, rustler::NifError> {
                 let env = term.get_env();
+                if !term.is_map() {
+                    return Err(rustler::NifError::expected("map"));
+                }
                 Ok(#struct_name { #(#field_defs),* })
             }
         }
@@ -65,11 +60,7 @@ pub fn gen_encoder(struct_name: &Ident, fields: &Vec<Field>, has_lifetime: bool)
         let field_ident = field.clone().ident.unwrap();
         let field_ident_str = field_ident.to_string();
         quote! {
-            map = rustler::map::map_put(
-                map,
-                rustler::atom::get_atom_init(#field_ident_str).to_term(env),
-                self.#field_ident.encode(env)
-                ).unwrap();
+            (rustler_atom!(#field_ident_str).to_term(env), self.#field_ident.encode(env))
         }
     }).collect();
 
@@ -80,14 +71,10 @@ pub fn gen_encoder(struct_name: &Ident, fields: &Vec<Field>, has_lifetime: bool)
     };
 
     quote! {
-        impl<'a> rustler::codegen_runtime::GeneratedNifTranscoder<'a, rustler::codegen_runtime::MapType> {
-
-        }
         impl<'b> rustler::NifEncoder for #struct_type {
             fn encode<'a>(&self, env: rustler::NifEnv<'a>) -> rustler::NifTerm<'a> {
-                let mut map = rustler::map::map_new(env);
-                #(field_defs)*
-                map
+                use rustler::NifEncoder;
+                rustler::types::map::make_map_from_pairs(env, &[#(#field_defs),*])
             }
         }
     }
@@ -25,7 +25,7 @@ pub fn transcoder_decorator(ast: &syn::MacroInput) -> Result<quote::Tokens, &str
 pub fn gen_decoder(struct_name: &Ident, fields: &Vec<Field>, is_tuple: bool, has_lifetime: bool) -> Tokens {
     // Make a decoder for each of the fields in the struct.
     let field_defs: Vec<Tokens> = fields.iter().enumerate().map(|(idx, field)| {
-        let decoder = quote! { try!(rustler::NifDecoder::decode(terms[#idx])) };
+        let decoder = quote! { try!(terms.get(#idx).unwrap().decode().map_err(|err| err.at_index(#idx))) };
 
         if is_tuple {
             unimplemented!();
@@ -43,14 +43,15 @@ pub fn gen_decoder(struct_name: &Ident, fields: &Vec<Field>, is_tuple: bool, has
     };
 
     let field_num = field_defs.len();
+    let expected_tuple = format!("tuple of size {}", field_num);
 
     // The implementation itself
     quote! {
         impl<'a> rustler::NifDecoder<'a> for #struct_typ {
             fn decode(term: rustler::NifTerm<'a>) -> Result<Self, rustler::NifError> {
-                let terms = try!(rustler::types::tuple::get_tuple(term));
+                let terms = try!(rustler::types::tuple::get_tuple_ref(term));
                 if terms.len() != #field_num {
-                    return Err(rustler::NifError::BadArg);
+                    return Err(rustler::NifError::expected(#expected_tuple));
                 }
                 Ok(
                     #struct_name {
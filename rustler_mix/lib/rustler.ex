@@ -17,6 +17,15 @@ defmodule Rustler do
     "#{nif_lib_dir(application)}/lib#{lib_name}"
   end
 
+  @doc """
+  Loads the NIF library `lib_name` from the priv directory of `app_name`.
+
+  `load_args` is passed to the load function of the library, which can decode it into any type,
+  for example a map with configuration.
+
+  If the load function fails, `{:error, {:load, message}}` is returned, and the reason the
+  load function gave is written to stderr.
+  """
   defmacro load_nif(app_name, lib_name, load_args \\ nil) do
     quote do
       :erlang.load_nif(Rustler.nif_lib_path(unquote(app_name), unquote(lib_name)), unquote(load_args))
@@ -6,7 +6,7 @@ rustler_export_nifs!(
     None
 );
 
-fn add<'a>(env: &'a NifEnv, args: &Vec<NifTerm>) -> NifResult<NifTerm<'a>> {
+fn add<'a>(env: &'a NifEnv, args: &[NifTerm]) -> NifResult<NifTerm<'a>> {
     let num1: i64 = try!(args[0].decode());
     let num2: i64 = try!(args[1].decode());
     Ok((num1 + num2).encode(env))
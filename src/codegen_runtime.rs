@@ -1,10 +1,20 @@
 //! Functions used by runtime generated code. Should not be used.
 
-use ::{NifEnv, NifTerm};
-use ::types::atom::get_atom_init;
-use std::panic::catch_unwind;
+use ::{NifEnv, NifTerm, NifError, NifDecoder, NifEncoder};
+use ::types::atom::{ self, make_atom_term, init_atom_caches };
+use ::types::tuple::make_tuple;
+use std::ptr;
+use std::mem::MaybeUninit;
+use std::panic::{ catch_unwind, AssertUnwindSafe, UnwindSafe };
+use std::any::Any;
+use std::io::{ self, Write };
+use std;
 use ::wrapper::exception;
+use ::wrapper::atom::try_make_atom;
 use ::resource::NifResourceTypeProvider;
+use ::priv_data::{ with_priv_data_slot, drop_priv_data };
+use ::panic::{ install_panic_hook, uninstall_panic_hook, catch_nif_panic, nif_panic_term, panic_message,
+               panic_reason };
 use ::NifResult;
 
 // Names used by the `rustler_export_nifs!` macro or other generated code.
@@ -13,53 +23,221 @@ pub use ::wrapper::nif_interface::{
     NIF_ENV, NIF_TERM, NIF_MAJOR_VERSION, NIF_MINOR_VERSION,
     MUTABLE_NIF_RESOURCE_HANDLE };
 
+/// The type of the functions exported with `rustler_export_nifs!`.
+pub type NifFunction = for<'a> fn(NifEnv<'a>, &[NifTerm<'a>]) -> NifResult<NifTerm<'a>>;
+
+// The highest arity the VM allows. The arguments of a NIF are wrapped in a stack array of this
+// size, of which only the part that is used gets written.
+const MAX_ARITY: usize = 255;
+
 // This is the last level of rust safe rust code before the BEAM.
 // No panics should go above this point, as they will unwrap into the C code and ruin the day.
-pub fn handle_nif_call(function: for<'a> fn(NifEnv<'a>, &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>>,
-                       _arity: usize, r_env: NIF_ENV,
+pub fn handle_nif_call(function: NifFunction,
+                       name: &'static str, arity: usize, r_env: NIF_ENV,
                        argc: c_int, argv: *const NIF_TERM) -> NIF_TERM {
     let env_lifetime = ();
     let env = unsafe { NifEnv::new(&env_lifetime, r_env) };
 
-    let terms = unsafe { ::std::slice::from_raw_parts(argv, argc as usize) }.iter()
-        .map(|x| NifTerm::new(env, *x)).collect::<Vec<NifTerm>>();
-
-    let result: ::std::thread::Result<NIF_TERM> = catch_unwind(|| {
-        match function(env, &terms) {
-            Ok(ret) => ret.as_c_arg(),
-            Err(err) => unsafe { err.encode(env) }.as_c_arg(),
+    let args = unsafe { ::std::slice::from_raw_parts(argv, argc as usize) };
+    let result = if args.len() <= MAX_ARITY {
+        let mut terms: [MaybeUninit<NifTerm>; MAX_ARITY] = unsafe { MaybeUninit::uninit().assume_init() };
+        for (term, &arg) in terms.iter_mut().zip(args) {
+            *term = MaybeUninit::new(NifTerm::new(env, arg));
         }
-    });
+        // The first `args.len()` terms were written above.
+        let terms = unsafe { ::std::slice::from_raw_parts(terms.as_ptr() as *const NifTerm, args.len()) };
+        call_nif(function, env, terms, args)
+    } else {
+        call_nif_with_vec(function, env, args)
+    };
 
     match result {
         Ok(res) => res,
-        Err(_err) => unsafe {
-            exception::raise_exception(
-                env.as_c_arg(),
-                get_atom_init("nif_panic").to_term(env).as_c_arg())
+        Err(err) => {
+            let (term, raise) = nif_panic_term(env, &err, name, arity);
+            if raise {
+                unsafe { exception::raise_exception(env.as_c_arg(), term.as_c_arg()) }
+            } else {
+                term.as_c_arg()
+            }
         },
     }
 }
 
-pub fn handle_nif_init_call(function: Option<for<'a> fn(NifEnv<'a>, NifTerm<'a>) -> bool>,
+// Only reached if the VM passes more arguments than it allows a NIF to have. Kept out of
+// `handle_nif_call`, which is then faster.
+#[inline(never)]
+fn call_nif_with_vec<'a>(function: NifFunction, env: NifEnv<'a>, args: &[NIF_TERM])
+                         -> ::std::thread::Result<NIF_TERM> {
+    let terms: Vec<NifTerm> = args.iter().map(|&arg| NifTerm::new(env, arg)).collect();
+    call_nif(function, env, &terms, args)
+}
+
+fn call_nif<'a>(function: NifFunction, env: NifEnv<'a>, terms: &[NifTerm<'a>], args: &[NIF_TERM])
+                -> ::std::thread::Result<NIF_TERM> {
+    catch_nif_panic(|| {
+        match function(env, terms) {
+            Ok(ret) => ret.as_c_arg(),
+            Err(err) => unsafe { err.in_args(args).encode(env) }.as_c_arg(),
+        }
+    })
+}
+
+/// A load function, wrapped by `rustler_export_nifs!` to take a plain `NifTerm`.
+pub type NifLoadHook = for<'a> fn(NifEnv<'a>, NifTerm<'a>) -> Result<(), Option<NifError>>;
+
+/// The return value of a load function, converted into the reason for a failed load, if any.
+/// Load functions returning `false` fail without a reason.
+pub trait NifLoadResult {
+    fn into_load_result(self) -> Result<(), Option<NifError>>;
+}
+
+impl NifLoadResult for bool {
+    fn into_load_result(self) -> Result<(), Option<NifError>> {
+        if self { Ok(()) } else { Err(None) }
+    }
+}
+
+impl NifLoadResult for NifResult<()> {
+    fn into_load_result(self) -> Result<(), Option<NifError>> {
+        self.map_err(Some)
+    }
+}
+
+/// Decodes `load_info` into the type taken by a load function, and calls it.
+pub fn call_load_hook<'a, F, T, R>(function: F, env: NifEnv<'a>, load_info: NifTerm<'a>)
+                                   -> Result<(), Option<NifError>>
+    where F: FnOnce(NifEnv<'a>, T) -> R,
+          T: NifDecoder<'a>,
+          R: NifLoadResult
+{
+    let decoded = try!(load_info.decode::<T>()
+                       .map_err(|err| Some(err.in_named("load_info", load_info.as_c_arg()))));
+    function(env, decoded).into_load_result()
+}
+
+pub fn handle_nif_init_call(function: Option<NifLoadHook>,
                             r_env: NIF_ENV,
+                            priv_data: *mut *mut c_void,
                             load_info: NIF_TERM) -> c_int {
     let env_lifetime = ();
     let env = unsafe { NifEnv::new(&env_lifetime, r_env) };
+
+    match run_load_hook(function, env, priv_data, load_info) {
+        Ok(()) => 0,
+        Err(None) => 1,
+        Err(Some(reason)) => {
+            // The VM only reports the numeric return value of a failed load function, and closes
+            // the library right after, so the reason can only be logged.
+            let _ = writeln!(io::stderr(), "rustler: load failed: {:?}", reason);
+            1
+        },
+    }
+}
+
+pub fn handle_nif_upgrade_call(function: Option<NifLoadHook>,
+                               load_function: Option<NifLoadHook>,
+                               r_env: NIF_ENV,
+                               priv_data: *mut *mut c_void,
+                               load_info: NIF_TERM) -> c_int {
+    let env_lifetime = ();
+    let env = unsafe { NifEnv::new(&env_lifetime, r_env) };
+
+    // Without a dedicated upgrade function, the new library is set up like on a fresh load. A
+    // failed upgrade always fails, so the previous version of the library keeps being used.
+    match run_load_hook(function.or(load_function), env, priv_data, load_info) {
+        Ok(()) => 0,
+        Err(None) => 1,
+        Err(Some(reason)) => {
+            let _ = writeln!(io::stderr(), "rustler: upgrade failed: {:?}", reason);
+            1
+        },
+    }
+}
+
+// Runs a load or upgrade function, returning the reason it failed, if it gave one.
+fn run_load_hook<'a>(function: Option<NifLoadHook>, env: NifEnv<'a>, priv_data: *mut *mut c_void,
+                     load_info: NIF_TERM) -> Result<(), Option<NifTerm<'a>>> {
     let term = NifTerm::new(env, load_info);
 
+    // Before any NIF runs, so the atoms of `atoms!` are only read afterwards.
+    init_atom_caches();
+
     if let Some(inner) = function {
-        if inner(env, term) { 0 } else { 1 }
+        let result = catch_unwind(|| with_priv_data_slot(priv_data, || inner(env, term)));
+        match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(None)) => Err(None),
+            Ok(Err(Some(err))) => Err(Some(load_error_reason(env, err))),
+            Err(err) => Err(Some(make_tuple(env, &[
+                atom::nif_panic().to_term(env),
+                panic_reason(env, &err),
+            ]))),
+        }
     } else {
-        0
+        Ok(())
     }
 }
 
+fn load_error_reason<'a>(env: NifEnv<'a>, err: NifError) -> NifTerm<'a> {
+    match err {
+        NifError::BadArg => atom::badarg().to_term(env),
+        NifError::Atom(atom_str) | NifError::RaiseAtom(atom_str) => make_atom_term(env, atom_str),
+        NifError::AtomString(atom_string) | NifError::RaiseAtomString(atom_string) =>
+            match unsafe { try_make_atom(env.as_c_arg(), &atom_string) } {
+                Ok(atom) => NifTerm::new(env, atom),
+                Err(err) => load_error_reason(env, err),
+            },
+        NifError::RaiseTerm(ref term_unencoded) | NifError::Term(ref term_unencoded) =>
+            term_unencoded.encode(env),
+        NifError::Decode(ref decode_error) =>
+            make_tuple(env, &[atom::badarg().to_term(env), decode_error.to_string().encode(env)]),
+    }
+}
+
+// Used where a panic can't be reported back to Erlang code, like in destructors.
+fn log_panic(context: &str, err: &Box<dyn Any + Send>) {
+    let message = panic_message(err).unwrap_or("Box<Any>");
+    let _ = writeln!(io::stderr(), "rustler: panic in {}: {}", context, message);
+}
+
+/// Builds the `ErlNifEntry` of the library. If that panics, a null pointer is returned, which
+/// makes the VM fail the load.
+pub fn handle_nif_init<F>(function: F) -> *const DEF_NIF_ENTRY
+    where F: FnOnce() -> *const DEF_NIF_ENTRY + UnwindSafe
+{
+    install_panic_hook();
+    match catch_unwind(function) {
+        Ok(entry) => entry,
+        Err(err) => {
+            log_panic("nif_init", &err);
+            ptr::null()
+        },
+    }
+}
+
+pub fn handle_nif_unload_call(function: Option<for<'a> fn(NifEnv<'a>)>,
+                              r_env: NIF_ENV,
+                              priv_data: *mut c_void) {
+    let env_lifetime = ();
+    let env = unsafe { NifEnv::new(&env_lifetime, r_env) };
+
+    if let Some(inner) = function {
+        if let Err(err) = catch_unwind(|| inner(env)) {
+            log_panic("unload", &err);
+        }
+    }
+    if let Err(err) = catch_unwind(|| unsafe { drop_priv_data(priv_data) }) {
+        log_panic("drop of private data", &err);
+    }
+    uninstall_panic_hook();
+}
 
-use std;
 use ::resource::align_alloced_mem_for_struct;
 pub unsafe fn handle_drop_resource_struct_handle<T: NifResourceTypeProvider>(_env: NIF_ENV, handle: MUTABLE_NIF_RESOURCE_HANDLE) {
     let aligned = align_alloced_mem_for_struct::<Box<T>>(handle);
     let res = aligned as *mut Box<T>;
-    std::mem::drop(std::ptr::read(res));
+    if let Err(err) = catch_unwind(AssertUnwindSafe(|| std::mem::drop(std::ptr::read(res)))) {
+        log_panic("resource destructor", &err);
+    }
 }
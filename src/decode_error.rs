@@ -0,0 +1,228 @@
+//! Errors from decoding terms.
+//!
+//! When a decoder fails, it returns a `NifError::Decode` that records the type it expected, and
+//! where in the decoded term the failure happened, like `args[1].items[3].price`. The decoders
+//! of containers such as `Vec<T>`, `HashMap<K, V>` and the derived decoders add the index, key or
+//! field to the path of an error coming from one of their elements.
+//!
+//! By default, a NIF returning a decode error raises `badarg`, like it does for `NifError::BadArg`.
+//! With `set_decode_error_mode()`, it can raise an `ArgumentError` describing the failure
+//! instead:
+//!
+//! ```text
+//! ** (ArgumentError) expected u32 at args[1].items[3].price
+//! ```
+//!
+//! Decoders written by hand can create decode errors with `NifError::expected()`:
+//!
+//! ```ignore
+//! impl<'a> NifDecoder<'a> for Color {
+//!     fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+//!         match term.atom_to_string()?.as_str() {
+//!             "red" => Ok(Color::Red),
+//!             "green" => Ok(Color::Green),
+//!             _ => Err(NifError::expected(":red or :green")),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+
+use ::{ NifError, NifTerm };
+use ::setting::{ LibrarySetting, SettingValue };
+use ::wrapper::nif_interface::NIF_TERM;
+
+/// How a NIF call fails when the NIF returns a decode error.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NifDecodeErrorMode {
+    /// Raise `badarg`.
+    BadArg,
+    /// Raise an `ArgumentError` with a message saying what was expected where.
+    ArgumentError,
+}
+
+impl SettingValue for NifDecodeErrorMode {
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => NifDecodeErrorMode::BadArg,
+            _ => NifDecodeErrorMode::ArgumentError,
+        }
+    }
+}
+
+static DECODE_ERROR_MODE: LibrarySetting<NifDecodeErrorMode> = LibrarySetting::new();
+
+/// Sets the way all NIFs in this library fail when they return a decode error. The default is
+/// `NifDecodeErrorMode::BadArg`. A single error can fail differently with
+/// `NifError::with_decode_error_mode()`.
+pub fn set_decode_error_mode(mode: NifDecodeErrorMode) {
+    DECODE_ERROR_MODE.set(mode);
+}
+
+/// Returns the current decode error mode of this library.
+pub fn decode_error_mode() -> NifDecodeErrorMode {
+    DECODE_ERROR_MODE.get()
+}
+
+enum PathSegment {
+    Index(usize),
+    Field(&'static str),
+    Key(String),
+}
+
+enum Root {
+    Unknown,
+    Arg(usize),
+    Named(&'static str),
+}
+
+/// A failure to decode a term, with the type that was expected and the path to the failing part
+/// of the term.
+pub struct NifDecodeError {
+    expected: &'static str,
+    // Innermost segment first, since segments are added while the error is passed outwards.
+    path: Vec<PathSegment>,
+    // The outermost term the error has been passed through `NifTerm::decode()` for.
+    term: Option<NIF_TERM>,
+    root: Root,
+    // Set by `NifError::with_decode_error_mode()`, instead of the mode of the library.
+    mode: Option<NifDecodeErrorMode>,
+}
+
+impl NifDecodeError {
+    pub fn new(expected: &'static str) -> Self {
+        NifDecodeError {
+            expected: expected,
+            path: Vec::new(),
+            term: None,
+            root: Root::Unknown,
+            mode: None,
+        }
+    }
+
+    /// The type that was expected, like `"u32"` or `"list"`.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// The path to the part of the term that failed to decode, like `args[1].items[3].price`.
+    /// Empty if the whole term failed to decode, and the term is not known to be an argument.
+    pub fn path(&self) -> String {
+        let mut path = match self.root {
+            Root::Unknown => String::new(),
+            Root::Arg(index) => format!("args[{}]", index),
+            Root::Named(name) => name.to_string(),
+        };
+        for segment in self.path.iter().rev() {
+            match *segment {
+                PathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+                PathSegment::Key(ref key) => path.push_str(&format!("[{}]", key)),
+                PathSegment::Field(name) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                },
+            }
+        }
+        path
+    }
+
+    /// The way the NIF call fails with this error.
+    pub fn mode(&self) -> NifDecodeErrorMode {
+        self.mode.unwrap_or_else(decode_error_mode)
+    }
+}
+
+impl fmt::Display for NifDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = self.path();
+        if path.is_empty() {
+            write!(f, "expected {}", self.expected)
+        } else {
+            write!(f, "expected {} at {}", self.expected, path)
+        }
+    }
+}
+
+impl fmt::Debug for NifDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NifDecodeError({})", self)
+    }
+}
+
+/// ## Decode errors
+impl NifError {
+
+    /// Makes a decode error for a term that is not of the `expected` type.
+    pub fn expected(expected: &'static str) -> NifError {
+        NifError::Decode(Box::new(NifDecodeError::new(expected)))
+    }
+
+    /// Adds a list or tuple index to the path of a decode error. Other errors are returned
+    /// unchanged.
+    pub fn at_index(self, index: usize) -> NifError {
+        self.map_decode_error(|err| err.path.push(PathSegment::Index(index)))
+    }
+
+    /// Adds a struct or map field to the path of a decode error. Other errors are returned
+    /// unchanged.
+    pub fn at_field(self, name: &'static str) -> NifError {
+        self.map_decode_error(|err| err.path.push(PathSegment::Field(name)))
+    }
+
+    /// Adds a map key to the path of a decode error, like `["name"]`. Other errors are returned
+    /// unchanged.
+    pub fn at_key(self, key: NifTerm) -> NifError {
+        self.map_decode_error(|err| err.path.push(PathSegment::Key(format!("{:?}", key))))
+    }
+
+    /// Makes a decode error fail the NIF call in the given way, instead of the way set for the
+    /// library with `set_decode_error_mode()`. Other errors are returned unchanged.
+    pub fn with_decode_error_mode(self, mode: NifDecodeErrorMode) -> NifError {
+        self.map_decode_error(|err| err.mode = Some(mode))
+    }
+
+    #[doc(hidden)]
+    pub fn decoded_from(self, term: NIF_TERM) -> NifError {
+        self.map_decode_error(|err| err.term = Some(term))
+    }
+
+    /// Marks a decode error as coming from one of the given NIF arguments, if it does.
+    #[doc(hidden)]
+    pub fn in_args(self, args: &[NIF_TERM]) -> NifError {
+        self.map_decode_error(|err| {
+            if let Some(term) = err.term {
+                if let Some(index) = args.iter().position(|arg| *arg == term) {
+                    err.root = Root::Arg(index);
+                }
+            }
+        })
+    }
+
+    /// Marks a decode error as coming from the given named term, if it does.
+    #[doc(hidden)]
+    pub fn in_named(self, name: &'static str, term: NIF_TERM) -> NifError {
+        self.map_decode_error(|err| {
+            if err.term == Some(term) {
+                err.root = Root::Named(name);
+            }
+        })
+    }
+
+    fn map_decode_error<F>(self, fun: F) -> NifError where F: FnOnce(&mut NifDecodeError) {
+        match self {
+            NifError::Decode(mut err) => {
+                fun(&mut err);
+                NifError::Decode(err)
+            },
+            other => other,
+        }
+    }
+
+}
@@ -1,12 +1,17 @@
 use ::{NifTerm};
 use ::wrapper::check;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum TermType {
     Atom,
     Binary,
+    /// A bitstring whose size in bits is not divisible by 8. Binaries are `Binary`.
+    Bitstring,
     EmptyList,
     Exception,
+    Float,
     Fun,
+    Integer,
     List,
     Map,
     Pid,
@@ -16,9 +21,43 @@ pub enum TermType {
     Unknown
 }
 
+/// Returns the type of a term.
+///
+/// With the `nif_version_2_15` feature, this is a single call to `enif_term_type()`, plus one more
+/// to tell binaries from other bitstrings and empty lists from other lists. Otherwise the type is
+/// found by checking for each type in turn.
+///
+/// `TermType::Exception` is only returned by the fallback, for the value NIFs return when raising
+/// an exception. It never appears in terms passed to a NIF.
 pub fn get_type(term: NifTerm) -> TermType {
+    get_type_impl(term)
+}
+
+#[cfg(all(feature = "nif_version_2_15", not(windows)))]
+fn get_type_impl(term: NifTerm) -> TermType {
+    let env = term.get_env().as_c_arg();
+    match unsafe { ::wrapper::nif_interface::enif_term_type(env, term.as_c_arg()) } {
+        1 => TermType::Atom,
+        2 => if term.is_binary() { TermType::Binary } else { TermType::Bitstring },
+        3 => TermType::Float,
+        4 => TermType::Fun,
+        5 => TermType::Integer,
+        6 => if term.is_empty_list() { TermType::EmptyList } else { TermType::List },
+        7 => TermType::Map,
+        8 => TermType::Pid,
+        9 => TermType::Port,
+        10 => TermType::Ref,
+        11 => TermType::Tuple,
+        _ => TermType::Unknown,
+    }
+}
+
+#[cfg(not(all(feature = "nif_version_2_15", not(windows))))]
+fn get_type_impl(term: NifTerm) -> TermType {
     if term.is_atom() {
         TermType::Atom
+    } else if term.is_number() {
+        if term.is_float() { TermType::Float } else { TermType::Integer }
     } else if term.is_binary() {
         TermType::Binary
     } else if term.is_empty_list() {
@@ -40,7 +79,9 @@ pub fn get_type(term: NifTerm) -> TermType {
     } else if term.is_tuple() {
         TermType::Tuple
     } else {
-        TermType::Unknown
+        // Bitstrings are the only type without a check of their own, so a term of no other type
+        // is a bitstring.
+        TermType::Bitstring
     }
 }
 
@@ -73,5 +114,7 @@ impl<'a> NifTerm<'a> {
     impl_check!(is_port);
     impl_check!(is_ref);
     impl_check!(is_tuple);
+    impl_check!(is_number);
+    impl_check!(is_float);
 
 }
@@ -2,6 +2,7 @@ use ::{ NifEnv, NifTerm };
 use wrapper::copy_term;
 use wrapper::nif_interface::{ self, NIF_ENV, NIF_TERM };
 pub use wrapper::nif_interface::ErlNifPid;
+use std::cell::RefCell;
 use std::mem;
 use std::sync::{Arc, Weak};
 
@@ -33,6 +34,34 @@ fn nif_term_into_env<'a>(term: NIF_TERM, src_env: NIF_ENV, dest_env: NIF_ENV) ->
 }
 
 
+thread_local! {
+    // The environments of the `OwnedEnv`s that are running a closure on this thread.
+    static RUNNING_OWNED_ENVS: RefCell<Vec<NIF_ENV>> = RefCell::new(Vec::new());
+}
+
+// Records an owned environment as running on this thread until it is dropped.
+struct RunningOwnedEnv;
+
+impl RunningOwnedEnv {
+    fn enter(env: NIF_ENV) -> Self {
+        RUNNING_OWNED_ENVS.with(|envs| envs.borrow_mut().push(env));
+        RunningOwnedEnv
+    }
+}
+
+impl Drop for RunningOwnedEnv {
+    fn drop(&mut self) {
+        RUNNING_OWNED_ENVS.with(|envs| envs.borrow_mut().pop());
+    }
+}
+
+/// Returns whether `env` belongs to an `OwnedEnv`. A `NifEnv` of an owned environment only exists
+/// while `run()` or `send()` is running a closure with it, on the same thread.
+pub fn is_process_independent(env: NifEnv) -> bool {
+    let c_env = env.as_c_arg();
+    RUNNING_OWNED_ENVS.with(|envs| envs.borrow().contains(&c_env))
+}
+
 /// A process-independent environment.
 ///
 /// An owned environment is a place where Erlang terms can be created outside of a NIF call. Rust
@@ -68,6 +97,7 @@ impl OwnedEnv {
     {
         let env_lifetime = ();
         let env = unsafe { NifEnv::new(&env_lifetime, *self.env) };
+        let _running = RunningOwnedEnv::enter(*self.env);
         closure(env)
     }
 
@@ -81,7 +111,10 @@ impl OwnedEnv {
         let env_lifetime = ();
         let c_env = *self.env;
         let env = unsafe { NifEnv::new(&env_lifetime, c_env) };
-        let message = closure(env);
+        let message = {
+            let _running = RunningOwnedEnv::enter(c_env);
+            closure(env)
+        };
 
         self.env = Arc::new(c_env);
         unsafe {
@@ -149,6 +182,8 @@ impl Drop for OwnedEnv {
 ///
 /// These are created by calling `OwnedEnv::save()`. See that method's documentation for an
 /// example.
+///
+/// To keep a term without managing an `OwnedEnv`, copy it into an `OwnedTerm` instead.
 #[derive(Clone)]
 pub struct SavedTerm {
     env_generation: Weak<NIF_ENV>,
@@ -0,0 +1,199 @@
+use std::str;
+
+use super::{ Term, BigInteger, DecodeError };
+use super::tag;
+
+/// The deepest nesting of lists, tuples and maps that `decode()` accepts. Decoding recurses once
+/// for every level, so this bounds the stack used by untrusted data.
+pub const MAX_DEPTH: usize = 256;
+
+/// Decodes a term from the external term format.
+///
+/// # Errors
+///
+/// If `data` is not exactly one encoded term, the term is nested deeper than `MAX_DEPTH`, or the
+/// term contains values that a `Term` can't represent.
+pub fn decode(data: &[u8]) -> Result<Term, DecodeError> {
+    let mut decoder = Decoder { data: data, depth: 0 };
+    let version = try!(decoder.u8());
+    if version != tag::VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    let term = try!(decoder.term());
+    if !decoder.data.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+    Ok(term)
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    // The number of terms being decoded that contain the current one.
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.data.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(try!(self.bytes(1))[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        let bytes = try!(self.bytes(2));
+        Ok(((bytes[0] as u16) << 8) | bytes[1] as u16)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let bytes = try!(self.bytes(4));
+        Ok(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u32))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        let bytes = try!(self.bytes(8));
+        Ok(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    fn term(&mut self) -> Result<Term, DecodeError> {
+        if self.depth == MAX_DEPTH {
+            return Err(DecodeError::TooDeep);
+        }
+        self.depth += 1;
+        let term = self.term_contents();
+        self.depth -= 1;
+        term
+    }
+
+    fn term_contents(&mut self) -> Result<Term, DecodeError> {
+        let tag = try!(self.u8());
+        match tag {
+            tag::SMALL_INTEGER_EXT =>
+                Ok(Term::Integer(try!(self.u8()) as i64)),
+            tag::INTEGER_EXT =>
+                Ok(Term::Integer(try!(self.u32()) as i32 as i64)),
+            tag::SMALL_BIG_EXT => {
+                let len = try!(self.u8()) as usize;
+                self.big_integer(len)
+            },
+            tag::LARGE_BIG_EXT => {
+                let len = try!(self.u32()) as usize;
+                self.big_integer(len)
+            },
+            tag::NEW_FLOAT_EXT =>
+                Ok(Term::Float(f64::from_bits(try!(self.u64())))),
+            tag::FLOAT_EXT => {
+                // The float printed as a string, padded with null bytes.
+                let bytes = try!(self.bytes(31));
+                let text = try!(str::from_utf8(bytes).map_err(|_| DecodeError::InvalidFloat));
+                let value = try!(text.trim_end_matches('\0').parse().map_err(|_| DecodeError::InvalidFloat));
+                Ok(Term::Float(value))
+            },
+            tag::SMALL_ATOM_EXT | tag::SMALL_ATOM_UTF8_EXT => {
+                let len = try!(self.u8()) as usize;
+                self.atom(len, tag == tag::SMALL_ATOM_UTF8_EXT)
+            },
+            tag::ATOM_EXT | tag::ATOM_UTF8_EXT => {
+                let len = try!(self.u16()) as usize;
+                self.atom(len, tag == tag::ATOM_UTF8_EXT)
+            },
+            tag::BINARY_EXT => {
+                let len = try!(self.u32()) as usize;
+                Ok(Term::Binary(try!(self.bytes(len)).to_vec()))
+            },
+            tag::NIL_EXT =>
+                Ok(Term::List(Vec::new())),
+            tag::STRING_EXT => {
+                // A list of small integers, like `'abc'`.
+                let len = try!(self.u16()) as usize;
+                let bytes = try!(self.bytes(len));
+                Ok(Term::List(bytes.iter().map(|&b| Term::Integer(b as i64)).collect()))
+            },
+            tag::LIST_EXT => {
+                let len = try!(self.u32()) as usize;
+                let items = try!(self.terms(len));
+                match try!(self.term()) {
+                    Term::List(ref tail) if tail.is_empty() => Ok(Term::List(items)),
+                    _ => Err(DecodeError::ImproperList),
+                }
+            },
+            tag::SMALL_TUPLE_EXT => {
+                let len = try!(self.u8()) as usize;
+                Ok(Term::Tuple(try!(self.terms(len))))
+            },
+            tag::LARGE_TUPLE_EXT => {
+                let len = try!(self.u32()) as usize;
+                Ok(Term::Tuple(try!(self.terms(len))))
+            },
+            tag::MAP_EXT => {
+                let len = try!(self.u32()) as usize;
+                let mut entries = Vec::with_capacity(self.capacity(len, 2));
+                for _ in 0..len {
+                    let key = try!(self.term());
+                    let value = try!(self.term());
+                    entries.push((key, value));
+                }
+                Ok(Term::Map(entries))
+            },
+            _ => Err(DecodeError::UnsupportedTag(tag)),
+        }
+    }
+
+    fn terms(&mut self, len: usize) -> Result<Vec<Term>, DecodeError> {
+        let mut terms = Vec::with_capacity(self.capacity(len, 1));
+        for _ in 0..len {
+            terms.push(try!(self.term()));
+        }
+        Ok(terms)
+    }
+
+    // Every encoded term takes at least a byte, so a length beyond the remaining data is invalid.
+    // This keeps a bad length from allocating a huge vector.
+    fn capacity(&self, len: usize, terms_per_item: usize) -> usize {
+        ::std::cmp::min(len, self.data.len() / terms_per_item)
+    }
+
+    fn atom(&mut self, len: usize, utf8: bool) -> Result<Term, DecodeError> {
+        let bytes = try!(self.bytes(len));
+        let name = if utf8 {
+            try!(str::from_utf8(bytes).map_err(|_| DecodeError::InvalidAtom)).to_string()
+        } else {
+            // Latin-1
+            bytes.iter().map(|&b| b as char).collect()
+        };
+        Ok(Term::Atom(name))
+    }
+
+    fn big_integer(&mut self, len: usize) -> Result<Term, DecodeError> {
+        let negative = match try!(self.u8()) {
+            0 => false,
+            _ => true,
+        };
+        let mut magnitude = try!(self.bytes(len)).to_vec();
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+
+        // Integers that fit in an `i64` always decode to `Term::Integer`.
+        if magnitude.len() <= 8 {
+            let value = magnitude.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u64);
+            if !negative && value <= i64::max_value() as u64 {
+                return Ok(Term::Integer(value as i64));
+            }
+            if negative && value <= 1 << 63 {
+                return Ok(Term::Integer((value as i64).wrapping_neg()));
+            }
+        }
+
+        Ok(Term::BigInteger(BigInteger {
+            negative: negative,
+            magnitude: magnitude,
+        }))
+    }
+}
@@ -0,0 +1,117 @@
+use super::{ Term, BigInteger };
+use super::tag;
+
+/// Encodes a term in the external term format.
+pub fn encode(term: &Term) -> Vec<u8> {
+    let mut out = vec![tag::VERSION];
+    encode_term(term, &mut out);
+    out
+}
+
+fn encode_term(term: &Term, out: &mut Vec<u8>) {
+    match *term {
+        Term::Atom(ref name) => encode_atom(name, out),
+        Term::Integer(value) => encode_integer(value, out),
+        Term::BigInteger(ref value) => encode_big_integer(value, out),
+        Term::Float(value) => {
+            out.push(tag::NEW_FLOAT_EXT);
+            push_u64(value.to_bits(), out);
+        },
+        Term::Binary(ref data) => {
+            out.push(tag::BINARY_EXT);
+            push_u32(data.len() as u32, out);
+            out.extend_from_slice(data);
+        },
+        Term::List(ref items) => {
+            if !items.is_empty() {
+                out.push(tag::LIST_EXT);
+                push_u32(items.len() as u32, out);
+                for item in items {
+                    encode_term(item, out);
+                }
+            }
+            out.push(tag::NIL_EXT);
+        },
+        Term::Tuple(ref items) => {
+            if items.len() < 256 {
+                out.push(tag::SMALL_TUPLE_EXT);
+                out.push(items.len() as u8);
+            } else {
+                out.push(tag::LARGE_TUPLE_EXT);
+                push_u32(items.len() as u32, out);
+            }
+            for item in items {
+                encode_term(item, out);
+            }
+        },
+        Term::Map(ref entries) => {
+            out.push(tag::MAP_EXT);
+            push_u32(entries.len() as u32, out);
+            for &(ref key, ref value) in entries {
+                encode_term(key, out);
+                encode_term(value, out);
+            }
+        },
+        Term::Other(ref data) => out.extend_from_slice(data),
+    }
+}
+
+fn encode_atom(name: &str, out: &mut Vec<u8>) {
+    if name.len() < 256 {
+        out.push(tag::SMALL_ATOM_UTF8_EXT);
+        out.push(name.len() as u8);
+    } else {
+        out.push(tag::ATOM_UTF8_EXT);
+        push_u16(name.len() as u16, out);
+    }
+    out.extend_from_slice(name.as_bytes());
+}
+
+fn encode_integer(value: i64, out: &mut Vec<u8>) {
+    if value >= 0 && value < 256 {
+        out.push(tag::SMALL_INTEGER_EXT);
+        out.push(value as u8);
+    } else if value >= i32::min_value() as i64 && value <= i32::max_value() as i64 {
+        out.push(tag::INTEGER_EXT);
+        push_u32(value as u32, out);
+    } else {
+        // The magnitude of `i64::min_value()` doesn't fit in an `i64`, but does in a `u64`.
+        let mut magnitude = value.wrapping_abs() as u64;
+        let mut bytes = Vec::with_capacity(8);
+        while magnitude > 0 {
+            bytes.push(magnitude as u8);
+            magnitude >>= 8;
+        }
+        out.push(tag::SMALL_BIG_EXT);
+        out.push(bytes.len() as u8);
+        out.push((value < 0) as u8);
+        out.extend_from_slice(&bytes);
+    }
+}
+
+fn encode_big_integer(value: &BigInteger, out: &mut Vec<u8>) {
+    if value.magnitude.len() < 256 {
+        out.push(tag::SMALL_BIG_EXT);
+        out.push(value.magnitude.len() as u8);
+    } else {
+        out.push(tag::LARGE_BIG_EXT);
+        push_u32(value.magnitude.len() as u32, out);
+    }
+    out.push(value.negative as u8);
+    out.extend_from_slice(&value.magnitude);
+}
+
+fn push_u16(value: u16, out: &mut Vec<u8>) {
+    out.push((value >> 8) as u8);
+    out.push(value as u8);
+}
+
+fn push_u32(value: u32, out: &mut Vec<u8>) {
+    push_u16((value >> 16) as u16, out);
+    push_u16(value as u16, out);
+}
+
+fn push_u64(value: u64, out: &mut Vec<u8>) {
+    push_u32((value >> 32) as u32, out);
+    push_u32(value as u32, out);
+}
@@ -0,0 +1,289 @@
+//! A pure Rust codec for the Erlang external term format.
+//!
+//! `Term` is a plain Rust value holding an Erlang term. Unlike `NifTerm`, it does not belong to a
+//! `NifEnv`, so it can be built, stored and sent between threads without using the VM at all:
+//!
+//! ```ignore
+//! use rustler::etf::Term;
+//!
+//! let mut msg_env = OwnedEnv::new();
+//! thread::spawn(move || {
+//!     // No NIF API calls are needed to build the reply.
+//!     let reply = Term::Tuple(vec![Term::atom("ok"), Term::from("done"), Term::Integer(42)]);
+//!     msg_env.send(pid, |env| reply.encode(env));
+//! });
+//! ```
+//!
+//! A `Term` is loaded into an environment by decoding its external term format in a single call
+//! to `enif_binary_to_term()`, and a `NifTerm` is decoded into a `Term` by copying it with
+//! `Term::from_term()`. `encode()` and `decode()` convert between `Term`s and the external term
+//! format directly.
+//!
+//! Atoms, integers of any size, floats, binaries, proper lists, tuples and maps have variants of
+//! their own. Other terms, like pids, references, funs, bitstrings and improper lists, are kept
+//! in the external term format as `Term::Other` when a `NifTerm` is copied. `decode()` rejects
+//! them, and compressed data can't be decoded.
+//!
+//! `decode()` rejects lists, tuples and maps nested deeper than `MAX_DEPTH`, so it can be given
+//! untrusted data without running out of stack.
+
+use std::error::Error;
+use std::fmt;
+
+use ::{ NifEnv, NifTerm, NifError, NifResult, NifEncoder, NifDecoder };
+use ::dynamic::TermType;
+use ::exception::NifException;
+use ::types::binary::NifBinary;
+use ::types::map::NifMapIterator;
+use ::types::tuple::get_tuple_ref;
+
+mod encode;
+mod decode;
+
+pub use self::encode::encode;
+pub use self::decode::{ decode, MAX_DEPTH };
+
+#[cfg(test)]
+mod tests;
+
+// The tags of the external term format.
+#[doc(hidden)]
+pub mod tag {
+    pub const VERSION: u8 = 131;
+    pub const NEW_FLOAT_EXT: u8 = 70;
+    pub const BIT_BINARY_EXT: u8 = 77;
+    pub const SMALL_INTEGER_EXT: u8 = 97;
+    pub const INTEGER_EXT: u8 = 98;
+    pub const FLOAT_EXT: u8 = 99;
+    pub const ATOM_EXT: u8 = 100;
+    pub const SMALL_TUPLE_EXT: u8 = 104;
+    pub const LARGE_TUPLE_EXT: u8 = 105;
+    pub const NIL_EXT: u8 = 106;
+    pub const STRING_EXT: u8 = 107;
+    pub const LIST_EXT: u8 = 108;
+    pub const BINARY_EXT: u8 = 109;
+    pub const SMALL_BIG_EXT: u8 = 110;
+    pub const LARGE_BIG_EXT: u8 = 111;
+    pub const SMALL_ATOM_EXT: u8 = 115;
+    pub const MAP_EXT: u8 = 116;
+    pub const ATOM_UTF8_EXT: u8 = 118;
+    pub const SMALL_ATOM_UTF8_EXT: u8 = 119;
+}
+
+/// An Erlang term owned by Rust.
+///
+/// Comparing `Term`s is structural, so unlike in Erlang, `Integer(1)` is not equal to
+/// `Float(1.0)`, and maps with the same entries in a different order are not equal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Term {
+    Atom(String),
+    /// An integer that fits in an `i64`.
+    Integer(i64),
+    /// An integer that doesn't fit in an `i64`.
+    BigInteger(BigInteger),
+    Float(f64),
+    Binary(Vec<u8>),
+    List(Vec<Term>),
+    Tuple(Vec<Term>),
+    /// The entries of a map, in no particular order.
+    Map(Vec<(Term, Term)>),
+    /// A term without a variant of its own, like a pid or an improper list, in the external term
+    /// format without the leading version number.
+    Other(Vec<u8>),
+}
+
+/// An integer of any size, stored as its sign and magnitude.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigInteger {
+    pub negative: bool,
+    /// The bytes of the magnitude, least significant first.
+    pub magnitude: Vec<u8>,
+}
+
+impl Term {
+    pub fn atom<S: Into<String>>(name: S) -> Term {
+        Term::Atom(name.into())
+    }
+
+    /// Copies a term and all terms in it.
+    ///
+    /// Terms nested deeper than `MAX_DEPTH` are kept as `Term::Other`, so copying a term from an
+    /// untrusted caller can't run out of stack.
+    pub fn from_term(term: NifTerm) -> Term {
+        Term::from_term_at(term, 0)
+    }
+
+    fn from_term_at(term: NifTerm, depth: usize) -> Term {
+        if depth == MAX_DEPTH {
+            return Term::other(term);
+        }
+        let depth = depth + 1;
+        match term.get_type() {
+            TermType::Atom =>
+                match term.atom_to_string() {
+                    Ok(name) => Term::Atom(name),
+                    Err(_) => Term::other(term),
+                },
+            TermType::Integer =>
+                match term.decode() {
+                    Ok(value) => Term::Integer(value),
+                    // A big integer. Its encoding is decoded like any other.
+                    Err(_) => decode(term.to_binary().as_slice()).unwrap_or_else(|_| Term::other(term)),
+                },
+            TermType::Float =>
+                match term.decode() {
+                    Ok(value) => Term::Float(value),
+                    Err(_) => Term::other(term),
+                },
+            TermType::Binary =>
+                match NifBinary::from_term(term) {
+                    Ok(binary) => Term::Binary(binary.as_slice().to_vec()),
+                    Err(_) => Term::other(term),
+                },
+            TermType::EmptyList =>
+                Term::List(Vec::new()),
+            TermType::List => {
+                let mut items = Vec::new();
+                let mut tail = term;
+                while let Ok((head, rest)) = tail.list_get_cell() {
+                    items.push(Term::from_term_at(head, depth));
+                    tail = rest;
+                }
+                if tail.is_empty_list() { Term::List(items) } else { Term::other(term) }
+            },
+            TermType::Tuple =>
+                match get_tuple_ref(term) {
+                    Ok(items) => Term::Tuple(items.iter().map(|item| Term::from_term_at(item, depth)).collect()),
+                    Err(_) => Term::other(term),
+                },
+            TermType::Map =>
+                match NifMapIterator::new(term) {
+                    Some(iter) => Term::Map(iter.map(|(key, value)| {
+                        (Term::from_term_at(key, depth), Term::from_term_at(value, depth))
+                    }).collect()),
+                    None => Term::other(term),
+                },
+            _ =>
+                Term::other(term),
+        }
+    }
+
+    fn other(term: NifTerm) -> Term {
+        Term::Other(term.to_binary().as_slice()[1..].to_vec())
+    }
+
+    /// Makes the term in the given environment.
+    ///
+    /// # Errors
+    ///
+    /// An `ArgumentError` if the VM can't make the term, like for atoms with too long names, or
+    /// `Other` data that is not in the external term format.
+    pub fn load<'a>(&self, env: NifEnv<'a>) -> NifResult<NifTerm<'a>> {
+        match env.binary_to_term(&encode(self)) {
+            Some((term, _)) => Ok(term),
+            None => Err(NifException::argument_error("the VM can't make the term".to_string()).into()),
+        }
+    }
+}
+
+impl From<i64> for Term {
+    fn from(value: i64) -> Term {
+        Term::Integer(value)
+    }
+}
+
+impl From<f64> for Term {
+    fn from(value: f64) -> Term {
+        Term::Float(value)
+    }
+}
+
+impl From<bool> for Term {
+    fn from(value: bool) -> Term {
+        Term::atom(if value { "true" } else { "false" })
+    }
+}
+
+/// Strings are binaries, like when encoding a `&str` into a `NifTerm`.
+impl<'a> From<&'a str> for Term {
+    fn from(value: &'a str) -> Term {
+        Term::Binary(value.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for Term {
+    fn from(value: String) -> Term {
+        Term::Binary(value.into_bytes())
+    }
+}
+
+impl From<Vec<Term>> for Term {
+    fn from(value: Vec<Term>) -> Term {
+        Term::List(value)
+    }
+}
+
+/// # Panics
+///
+/// If the VM can't make the term. Use `Term::load()` to handle this case.
+impl NifEncoder for Term {
+    fn encode<'a>(&self, env: NifEnv<'a>) -> NifTerm<'a> {
+        match self.load(env) {
+            Ok(term) => term,
+            Err(_) => panic!("the VM can't make the term {:?}", self),
+        }
+    }
+}
+
+impl<'a> NifDecoder<'a> for Term {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        Ok(Term::from_term(term))
+    }
+}
+
+/// An error from decoding the external term format.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeError {
+    /// The data ended in the middle of a term.
+    UnexpectedEnd,
+    /// There were bytes left after the term.
+    TrailingBytes,
+    /// The data does not start with the version number 131.
+    UnsupportedVersion(u8),
+    /// A term with this tag can't be decoded, like a pid. Only `Term::from_term()` makes
+    /// `Term::Other`s.
+    UnsupportedTag(u8),
+    /// A list ended with a tail other than `[]`.
+    ImproperList,
+    /// Lists, tuples and maps are nested deeper than `MAX_DEPTH`.
+    TooDeep,
+    InvalidAtom,
+    InvalidFloat,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of data"),
+            DecodeError::TrailingBytes => write!(f, "trailing bytes after the term"),
+            DecodeError::UnsupportedVersion(version) => write!(f, "unsupported version {}", version),
+            DecodeError::UnsupportedTag(tag) => write!(f, "unsupported tag {}", tag),
+            DecodeError::ImproperList => write!(f, "improper list"),
+            DecodeError::TooDeep => write!(f, "terms nested too deeply"),
+            DecodeError::InvalidAtom => write!(f, "invalid atom"),
+            DecodeError::InvalidFloat => write!(f, "invalid float"),
+        }
+    }
+}
+
+impl Error for DecodeError {
+    fn description(&self) -> &str {
+        "invalid external term format"
+    }
+}
+
+impl From<DecodeError> for NifError {
+    fn from(err: DecodeError) -> NifError {
+        NifError::from_error(err)
+    }
+}
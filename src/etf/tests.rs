@@ -0,0 +1,113 @@
+use super::{ Term, BigInteger, DecodeError, MAX_DEPTH, encode, decode };
+use super::tag;
+
+fn roundtrip(term: Term) {
+    assert_eq!(decode(&encode(&term)), Ok(term));
+}
+
+#[test]
+fn roundtrip_terms() {
+    roundtrip(Term::atom("ok"));
+    roundtrip(Term::atom("héllo"));
+    roundtrip(Term::Integer(0));
+    roundtrip(Term::Integer(255));
+    roundtrip(Term::Integer(-1));
+    roundtrip(Term::Integer(i32::max_value() as i64 + 1));
+    roundtrip(Term::Integer(i64::max_value()));
+    roundtrip(Term::Integer(i64::min_value()));
+    roundtrip(Term::Float(-1.5));
+    roundtrip(Term::from("binary"));
+    roundtrip(Term::List(vec![]));
+    roundtrip(Term::List(vec![Term::Integer(1), Term::atom("a")]));
+    roundtrip(Term::Tuple(vec![]));
+    roundtrip(Term::Tuple((0..300).map(Term::Integer).collect()));
+    roundtrip(Term::Map(vec![(Term::atom("key"), Term::List(vec![Term::from("value")]))]));
+}
+
+#[test]
+fn encode_matches_the_vm() {
+    // :erlang.term_to_binary({:ok, [1, 1000]})
+    let data = [131, 104, 2, 119, 2, 111, 107, 108, 0, 0, 0, 2, 97, 1, 98, 0, 0, 3, 232, 106];
+    let term = Term::Tuple(vec![
+        Term::atom("ok"),
+        Term::List(vec![Term::Integer(1), Term::Integer(1000)]),
+    ]);
+    assert_eq!(encode(&term), data.to_vec());
+    assert_eq!(decode(&data), Ok(term));
+}
+
+#[test]
+fn big_integers() {
+    let big = Term::BigInteger(BigInteger { negative: true, magnitude: vec![0, 0, 0, 0, 0, 0, 0, 0, 1] });
+    roundtrip(big.clone());
+
+    // :erlang.term_to_binary(-(1 <<< 64))
+    assert_eq!(decode(&[131, 110, 9, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1]), Ok(big));
+
+    // Bignums that fit in an `i64` decode as integers, with leading zero bytes ignored.
+    assert_eq!(decode(&[131, 110, 2, 0, 1, 0]), Ok(Term::Integer(1)));
+    assert_eq!(decode(&[131, 110, 8, 1, 0, 0, 0, 0, 0, 0, 0, 128]), Ok(Term::Integer(i64::min_value())));
+}
+
+#[test]
+fn legacy_encodings() {
+    // 'ab', as a string of bytes.
+    assert_eq!(decode(&[131, 107, 0, 2, 97, 98]), Ok(Term::List(vec![Term::Integer(97), Term::Integer(98)])));
+    // A Latin-1 atom.
+    assert_eq!(decode(&[131, 100, 0, 1, 233]), Ok(Term::atom("é")));
+}
+
+#[test]
+fn truncated_input() {
+    let data = encode(&Term::Tuple(vec![Term::from("binary"), Term::Integer(1000)]));
+    for len in 1..data.len() {
+        assert_eq!(decode(&data[..len]), Err(DecodeError::UnexpectedEnd));
+    }
+    assert_eq!(decode(&[]), Err(DecodeError::UnexpectedEnd));
+}
+
+#[test]
+fn invalid_input() {
+    assert_eq!(decode(&[130, 106]), Err(DecodeError::UnsupportedVersion(130)));
+    assert_eq!(decode(&[131, 106, 106]), Err(DecodeError::TrailingBytes));
+    // A pid.
+    assert_eq!(decode(&[131, 88]), Err(DecodeError::UnsupportedTag(88)));
+    // [1 | 2]
+    assert_eq!(decode(&[131, 108, 0, 0, 0, 1, 97, 1, 97, 2]), Err(DecodeError::ImproperList));
+    assert_eq!(decode(&[131, 119, 1, 255]), Err(DecodeError::InvalidAtom));
+    // A huge length with no data doesn't allocate.
+    assert_eq!(decode(&[131, 108, 255, 255, 255, 255]), Err(DecodeError::UnexpectedEnd));
+}
+
+#[test]
+fn nesting_limit() {
+    fn nested(depth: usize) -> Vec<u8> {
+        let mut data = vec![tag::VERSION];
+        for _ in 1..depth {
+            data.extend_from_slice(&[tag::SMALL_TUPLE_EXT, 1]);
+        }
+        data.push(tag::NIL_EXT);
+        data
+    }
+
+    assert!(decode(&nested(MAX_DEPTH)).is_ok());
+    assert_eq!(decode(&nested(MAX_DEPTH + 1)), Err(DecodeError::TooDeep));
+
+    // Deep enough to overflow the stack without the limit.
+    let mut data = vec![tag::VERSION];
+    for _ in 0..1000000 {
+        data.extend_from_slice(&[tag::LIST_EXT, 0, 0, 0, 1]);
+    }
+    assert_eq!(decode(&data), Err(DecodeError::TooDeep));
+}
+
+#[test]
+fn other_terms_are_spliced_in() {
+    // :erlang.term_to_binary(self()) without the version number
+    let pid = vec![88, 119, 13, 110, 111, 110, 111, 100, 101, 64, 110, 111, 104, 111, 115, 116, 0, 0, 0, 80, 0, 0, 0, 0, 0, 0, 0, 0];
+    let mut expected = vec![tag::VERSION, tag::SMALL_TUPLE_EXT, 2, tag::NIL_EXT];
+    expected.extend_from_slice(&pid);
+
+    let term = Term::Tuple(vec![Term::Other(vec![tag::NIL_EXT]), Term::Other(pid)]);
+    assert_eq!(encode(&term), expected);
+}
@@ -0,0 +1,108 @@
+//! Raising Elixir exceptions from NIFs.
+//!
+//! An Elixir exception is a struct with an `__exception__` field set to `true`. When a NIF raises
+//! one, `rescue` clauses, `Exception.message/1` and error logging handle it like any exception
+//! raised from Elixir code.
+//!
+//! The common exceptions from the Elixir standard library can be raised with the constructors on
+//! `NifException`:
+//!
+//! ```ignore
+//! fn parse<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+//!     let input: &str = args[0].decode()?;
+//!     if input.is_empty() {
+//!         return Err(NifException::argument_error("input must not be empty").into());
+//!     }
+//!     // ...
+//! }
+//! ```
+//!
+//! # Custom exceptions
+//! For an exception module defined with `defexception`, annotate a struct with
+//! `#[derive(NifException)]` and give the module name with `#[module = "Elixir.TheModule"]`. All
+//! fields of the struct must implement `NifEncoder`. The struct can then be converted into a
+//! `NifError` that raises it.
+//!
+//! ```ignore
+//! #[derive(NifException)]
+//! #[module = "Elixir.MyApp.ParseError"]
+//! struct ParseError {
+//!     message: String,
+//!     line: u32,
+//! }
+//!
+//! return Err(ParseError { message: "unexpected token".to_string(), line: 12 }.into());
+//! ```
+
+use ::{ NifEnv, NifTerm, NifEncoder, NifError };
+use super::types::atom::{ self, make_atom_term };
+use super::types::map::make_map_from_pairs;
+
+/// Makes an exception struct of the given module without any other fields.
+pub fn make_ex_exception<'a>(env: NifEnv<'a>, exception_module: &'static str) -> NifTerm<'a> {
+    make_ex_exception_with_fields(env, exception_module, &[])
+}
+
+/// Makes an exception struct of the given module with the given fields, as pairs of atom keys and
+/// values.
+pub fn make_ex_exception_with_fields<'a>(env: NifEnv<'a>, exception_module: &'static str,
+                                         fields: &[(NifTerm<'a>, NifTerm<'a>)]) -> NifTerm<'a> {
+    let mut pairs = vec![
+        (atom::__struct__().to_term(env), make_atom_term(env, exception_module)),
+        (atom::__exception__().to_term(env), true.encode(env)),
+    ];
+    pairs.extend_from_slice(fields);
+    make_map_from_pairs(env, &pairs)
+}
+
+/// An Elixir exception built at run time.
+pub struct NifException {
+    module: &'static str,
+    fields: Vec<(&'static str, Box<NifEncoder>)>,
+}
+
+impl NifException {
+    /// Creates an exception of the given module, like `"Elixir.MyApp.ParseError"`.
+    pub fn new(module: &'static str) -> Self {
+        NifException {
+            module: module,
+            fields: Vec::new(),
+        }
+    }
+
+    /// `%ArgumentError{message: message}`
+    pub fn argument_error<S: Into<String>>(message: S) -> Self {
+        NifException::new("Elixir.ArgumentError").field("message", message.into())
+    }
+
+    /// `%RuntimeError{message: message}`
+    pub fn runtime_error<S: Into<String>>(message: S) -> Self {
+        NifException::new("Elixir.RuntimeError").field("message", message.into())
+    }
+
+    /// `%SystemLimitError{message: message}`
+    pub fn system_limit_error<S: Into<String>>(message: S) -> Self {
+        NifException::new("Elixir.SystemLimitError").field("message", message.into())
+    }
+
+    /// Sets a field of the exception struct.
+    pub fn field<T>(mut self, name: &'static str, value: T) -> Self where T: NifEncoder + 'static {
+        self.fields.push((name, Box::new(value)));
+        self
+    }
+}
+
+impl NifEncoder for NifException {
+    fn encode<'a>(&self, env: NifEnv<'a>) -> NifTerm<'a> {
+        let fields: Vec<(NifTerm<'a>, NifTerm<'a>)> = self.fields.iter()
+            .map(|&(name, ref value)| (make_atom_term(env, name), value.encode(env)))
+            .collect();
+        make_ex_exception_with_fields(env, self.module, &fields)
+    }
+}
+
+impl From<NifException> for NifError {
+    fn from(exception: NifException) -> NifError {
+        NifError::RaiseTerm(Box::new(exception))
+    }
+}
@@ -12,47 +12,159 @@
 /// is the arity (number of arguments) of the exported function. The third argument is a
 /// indentifier of a rust function. This is where your actual NIF will be implemented.
 ///
-/// The third argument is an `Option<fn(env: &NifEnv, load_info: NifTerm) -> bool>`. If this is
-/// `Some`, the function will execute when the NIF is first loaded by the BEAM.
+/// NIF functions take their arguments as a `&[NifTerm]`. The slice is built in a stack array, so
+/// calling a NIF doesn't allocate, whatever its arity.
+///
+/// The third argument is an `Option` of a load function. If this is `Some`, the function will
+/// execute when the NIF is first loaded by the BEAM. Its first argument is the `NifEnv`, and its
+/// second argument can be of any type that implements `NifDecoder`, like a `NifTerm` or a
+/// `#[derive(NifMap)]` struct. It is decoded from the `load_info` term given to
+/// `:erlang.load_nif/2`. The load function returns either a `bool` or a `NifResult<()>`.
+///
+/// If the load function returns an error, or if `load_info` can't be decoded, loading fails and
+/// `:erlang.load_nif/2` returns `{:error, {:load, message}}`. The VM doesn't pass on the reason,
+/// so it is written to stderr. When `load_info` can't be decoded, the reason is
+/// `{:badarg, message}`, with a message like `"expected u32 at load_info.pool_size"`.
+///
+/// ```ignore
+/// #[derive(NifMap)]
+/// struct Config {
+///     pool_size: u32,
+/// }
+///
+/// fn on_load<'a>(env: NifEnv<'a>, config: Config) -> NifResult<()> {
+///     if config.pool_size == 0 {
+///         return Err(NifError::Atom("invalid_pool_size"));
+///     }
+///     env.set_priv_data(config);
+///     Ok(())
+/// }
+/// ```
+///
+/// Two more optional arguments can be given for hot code upgrades:
+///
+/// * An upgrade function, taking the same arguments as the load function, that is executed
+///   instead of the load function when the library is loaded as part of a code upgrade, while an
+///   older version of the library is still loaded for the module. If this is `None`, the load
+///   function is run.
+/// * An `Option<fn(env: NifEnv)>` that is executed when the library is unloaded, either because
+///   the module was purged or because it was replaced by a newer version.
+///
+/// The load and upgrade functions can set the private data of the library with
+/// `NifEnv::set_priv_data()`. It is dropped after the unload function has run. The private data
+/// of the previous library version is dropped when that version is unloaded.
+///
+/// Resource types registered with `resource_struct_init!` take over the existing instances of
+/// the previous library version, so resources created before an upgrade stay valid after it.
+///
+/// Panics never unwind into the VM. How a NIF call that panics fails is configured with
+/// `rustler::panic::set_panic_mode()`. A panic in the load or upgrade function fails the load with
+/// the reason `{:nif_panic, message}`. Panics in the unload function and in resource destructors
+/// are written to stderr.
+///
+/// The load, upgrade and unload arguments must be written out as `Some(function)` or `None`.
 #[macro_export]
 macro_rules! rustler_export_nifs {
-    ($name:expr, [$( $exported_nif:tt ),*], $on_load:expr) => {
+    // Collect the optional functions as `(Some(function))` or `(None)` token trees, so that the
+    // ones left out can be told apart from the given ones while generating code.
+    (internal_hooks, $name:expr, $nifs:tt, [$($hook:tt)*], None, $($rest:tt)*) => {
+        rustler_export_nifs!(internal_hooks, $name, $nifs, [$($hook)* (None)], $($rest)*);
+    };
+    (internal_hooks, $name:expr, $nifs:tt, [$($hook:tt)*], None) => {
+        rustler_export_nifs!(internal_hooks, $name, $nifs, [$($hook)* (None)],);
+    };
+    (internal_hooks, $name:expr, $nifs:tt, [$($hook:tt)*], Some($fun:expr), $($rest:tt)*) => {
+        rustler_export_nifs!(internal_hooks, $name, $nifs, [$($hook)* (Some($fun))], $($rest)*);
+    };
+    (internal_hooks, $name:expr, $nifs:tt, [$($hook:tt)*], Some($fun:expr)) => {
+        rustler_export_nifs!(internal_hooks, $name, $nifs, [$($hook)* (Some($fun))],);
+    };
+    (internal_hooks, $name:expr, $nifs:tt, [$on_load:tt],) => {
+        rustler_export_nifs!(internal_hooks, $name, $nifs, [$on_load (None) (None)],);
+    };
+    (internal_hooks, $name:expr, [$( $exported_nif:tt ),*], [$on_load:tt $on_upgrade:tt $on_unload:tt],) => {
         static mut NIF_ENTRY: Option<$crate::codegen_runtime::DEF_NIF_ENTRY> = None;
 
         #[no_mangle]
         pub extern "C" fn nif_init() -> *const $crate::codegen_runtime::DEF_NIF_ENTRY {
-            // TODO: If an unwrap ever happens, we will unwind right into C! Fix this!
-
             extern "C" fn nif_load(
                 env: $crate::codegen_runtime::NIF_ENV,
-                _priv_data: *mut *mut $crate::codegen_runtime::c_void,
+                priv_data: *mut *mut $crate::codegen_runtime::c_void,
+                load_info: $crate::codegen_runtime::NIF_TERM)
+                -> $crate::codegen_runtime::c_int {
+                    $crate::codegen_runtime::handle_nif_init_call(
+                        rustler_export_nifs!(internal_load_hook, $on_load),
+                        env, priv_data, load_info)
+                }
+
+            extern "C" fn nif_reload(
+                env: $crate::codegen_runtime::NIF_ENV,
+                priv_data: *mut *mut $crate::codegen_runtime::c_void,
+                load_info: $crate::codegen_runtime::NIF_TERM)
+                -> $crate::codegen_runtime::c_int {
+                    $crate::codegen_runtime::handle_nif_init_call(
+                        rustler_export_nifs!(internal_load_hook, $on_load),
+                        env, priv_data, load_info)
+                }
+
+            extern "C" fn nif_upgrade(
+                env: $crate::codegen_runtime::NIF_ENV,
+                priv_data: *mut *mut $crate::codegen_runtime::c_void,
+                _old_priv_data: *mut *mut $crate::codegen_runtime::c_void,
                 load_info: $crate::codegen_runtime::NIF_TERM)
                 -> $crate::codegen_runtime::c_int {
-                    $crate::codegen_runtime::handle_nif_init_call($on_load, env, load_info)
+                    $crate::codegen_runtime::handle_nif_upgrade_call(
+                        rustler_export_nifs!(internal_load_hook, $on_upgrade),
+                        rustler_export_nifs!(internal_load_hook, $on_load),
+                        env, priv_data, load_info)
+                }
+
+            extern "C" fn nif_unload(
+                env: $crate::codegen_runtime::NIF_ENV,
+                priv_data: *mut $crate::codegen_runtime::c_void) {
+                    $crate::codegen_runtime::handle_nif_unload_call(rustler_export_nifs!(internal_unload_hook, $on_unload), env, priv_data)
                 }
 
             const FUN_ENTRIES: &'static [$crate::codegen_runtime::DEF_NIF_FUNC] = &[
                 $(rustler_export_nifs!(internal, $exported_nif)),*
             ];
 
-            let entry = $crate::codegen_runtime::DEF_NIF_ENTRY {
-                major: $crate::codegen_runtime::NIF_MAJOR_VERSION,
-                minor: $crate::codegen_runtime::NIF_MINOR_VERSION,
-                name: concat!($name, "\x00") as *const str as *const u8,
-                num_of_funcs: FUN_ENTRIES.len() as $crate::codegen_runtime::c_int,
-                funcs: FUN_ENTRIES.as_ptr(),
-                load: Some(nif_load),
-                reload: None,
-                upgrade: None,
-                unload: None,
-                vm_variant: b"beam.vanilla\x00".as_ptr(),
-                options: 0,
-            };
-            unsafe { NIF_ENTRY = Some(entry) };
+            $crate::codegen_runtime::handle_nif_init(|| {
+                let entry = $crate::codegen_runtime::DEF_NIF_ENTRY {
+                    major: $crate::codegen_runtime::NIF_MAJOR_VERSION,
+                    minor: $crate::codegen_runtime::NIF_MINOR_VERSION,
+                    name: concat!($name, "\x00") as *const str as *const u8,
+                    num_of_funcs: FUN_ENTRIES.len() as $crate::codegen_runtime::c_int,
+                    funcs: FUN_ENTRIES.as_ptr(),
+                    load: Some(nif_load),
+                    reload: Some(nif_reload),
+                    upgrade: Some(nif_upgrade),
+                    unload: Some(nif_unload),
+                    vm_variant: b"beam.vanilla\x00".as_ptr(),
+                    options: 0,
+                };
+                unsafe { NIF_ENTRY = Some(entry) };
+
+                unsafe { NIF_ENTRY.as_ref().unwrap() }
+            })
+        }
+    };
+
+    ($name:expr, [$( $exported_nif:tt ),*], $($hooks:tt)*) => {
+        rustler_export_nifs!(internal_hooks, $name, [$( $exported_nif ),*], [], $($hooks)*);
+    };
 
-            unsafe { NIF_ENTRY.as_ref().unwrap() }
+    (internal_load_hook, (None)) => { None };
+    (internal_load_hook, (Some($fun:expr))) => {
+        {
+            fn load_hook<'a>(env: $crate::NifEnv<'a>, load_info: $crate::NifTerm<'a>) -> Result<(), Option<$crate::NifError>> {
+                $crate::codegen_runtime::call_load_hook($fun, env, load_info)
+            }
+            Some(load_hook)
         }
     };
+    (internal_unload_hook, (None)) => { None };
+    (internal_unload_hook, (Some($fun:expr))) => { Some($fun) };
 
     (internal, ($nif_name:expr, $nif_arity:expr, $nif_fun:path)) => {
         rustler_export_nifs!(internal, ($nif_name, $nif_arity, $nif_fun, $crate::schedule::NifScheduleFlags::Normal))
@@ -67,7 +179,7 @@ macro_rules! rustler_export_nifs {
                     argc: $crate::codegen_runtime::c_int,
                     argv: *const $crate::codegen_runtime::NIF_TERM)
                     -> $crate::codegen_runtime::NIF_TERM {
-                    $crate::codegen_runtime::handle_nif_call($nif_fun, $nif_arity, env, argc, argv)
+                    $crate::codegen_runtime::handle_nif_call($nif_fun, $nif_name, $nif_arity, env, argc, argv)
                 }
                 nif_func
             },
@@ -23,17 +23,22 @@
 //! rustler_mix](https://hexdocs.pm/rustler/basics.html).
 
 use std::marker::PhantomData;
+use std::error::Error;
+use std::sync::Mutex;
 
 mod wrapper;
 use wrapper::nif_interface::NIF_ENV;
 
-#[doc(hidden)]
-pub mod codegen_runtime;
+#[cfg(feature = "serde")]
+extern crate serde as serde_crate;
 
 #[macro_use]
-extern crate lazy_static;
-
 pub mod types;
+#[macro_use]
+mod term_macro;
+
+#[doc(hidden)]
+pub mod codegen_runtime;
 
 mod term;
 
@@ -44,9 +49,19 @@ pub mod resource;
 pub mod dynamic;
 pub mod schedule;
 pub mod env;
+pub mod priv_data;
+pub mod panic;
+pub mod exception;
+pub mod decode_error;
 pub mod thread;
+pub mod etf;
+pub mod owned_term;
+pub mod term_format;
+#[cfg(feature = "serde")]
+pub mod serde;
 
 mod export;
+mod setting;
 
 pub type NifResult<T> = Result<T, NifError>;
 
@@ -101,7 +116,7 @@ impl<'a> NifEnv<'a> {
     pub fn error_tuple<T>(self, reason: T) -> NifTerm<'a>
         where T: NifEncoder
     {
-        let error = types::atom::get_atom_init("error").to_term(self);
+        let error = types::atom::error().to_term(self);
         let reason_term = reason.encode(self);
         types::tuple::make_tuple(self, &[error, reason_term])
     }
@@ -110,18 +125,101 @@ impl<'a> NifEnv<'a> {
 /// Represents usual errors that can happen in a nif. This enables you
 /// to return an error from anywhere, even places where you don't have
 /// an NifEnv availible.
+///
+/// The errors of the standard library, like `io::Error` and `ParseIntError`, convert into a
+/// `NifError`, so the `?` operator can be used on them. Other errors implementing
+/// `std::error::Error` are converted with `NifError::from_error()`:
+///
+/// ```ignore
+/// let integer: i64 = string.parse()?;
+/// let config: Config = toml::from_str(&text).map_err(NifError::from_error)?;
+/// ```
+///
+/// By default, such an error is returned from the NIF as `{error, Message}`, where `Message` is
+/// the `Display` text of the error as a binary. This can be changed with
+/// `NifError::set_std_error_conversion()`.
 pub enum NifError {
     /// Returned when the NIF has been called with the wrong number or type of
     /// arguments.
     BadArg,
     /// Encodes the string into an atom and returns it from the NIF.
     Atom(&'static str),
+    /// Like `Atom`, for atom names that are only known at run time.
+    AtomString(String),
     RaiseAtom(&'static str),
+    /// Like `RaiseAtom`, for atom names that are only known at run time.
+    RaiseAtomString(String),
     RaiseTerm(Box<NifEncoder>),
+    /// Encodes the value and returns it from the NIF, without raising.
+    Term(Box<NifEncoder>),
+    /// Returned by decoders when a term is not of the expected type. Raises `badarg` or an
+    /// `ArgumentError`, depending on `decode_error::set_decode_error_mode()`.
+    Decode(Box<decode_error::NifDecodeError>),
+}
+
+fn default_std_error_conversion(err: &dyn Error) -> NifError {
+    NifError::Term(Box::new(ErrorTuple(err.to_string())))
+}
+
+// The `{error, Message}` tuple std errors are converted into by default.
+struct ErrorTuple(String);
+
+impl NifEncoder for ErrorTuple {
+    fn encode<'a>(&self, env: NifEnv<'a>) -> NifTerm<'a> {
+        let message = self.0.encode(env);
+        env.error_tuple(message)
+    }
 }
 
+type StdErrorConversion = fn(&dyn Error) -> NifError;
+
+// The conversion set with `set_std_error_conversion()`, or `None` for the default. Every NIF
+// library has its own copy of this crate, and therefore its own conversion.
+static STD_ERROR_CONVERSION: Mutex<Option<StdErrorConversion>> = Mutex::new(None);
+
+// Lets `?` convert the errors of the standard library into `NifError`s.
+macro_rules! impl_from_std_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for NifError {
+                fn from(err: $error) -> NifError {
+                    NifError::from_error(err)
+                }
+            }
+        )*
+    }
+}
+
+impl_from_std_error!(
+    std::io::Error,
+    std::fmt::Error,
+    std::num::ParseIntError,
+    std::num::ParseFloatError,
+    std::num::TryFromIntError,
+    std::str::ParseBoolError,
+    std::str::Utf8Error,
+    std::string::FromUtf8Error,
+    std::char::ParseCharError,
+    std::net::AddrParseError,
+    std::time::SystemTimeError
+);
+
 impl NifError {
 
+    /// Converts a `std::error::Error` into a `NifError`, with the conversion set for this library
+    /// by `set_std_error_conversion()`.
+    pub fn from_error<E: Error>(err: E) -> NifError {
+        let conversion = *STD_ERROR_CONVERSION.lock().unwrap_or_else(|err| err.into_inner());
+        conversion.unwrap_or(default_std_error_conversion)(&err)
+    }
+
+    /// Sets the function that `from_error()` converts `std::error::Error`s into `NifError`s with,
+    /// for all NIFs in this library. For example, to raise them instead of returning
+    /// `{error, Message}`.
+    pub fn set_std_error_conversion(conversion: StdErrorConversion) {
+        *STD_ERROR_CONVERSION.lock().unwrap_or_else(|err| err.into_inner()) = Some(conversion);
+    }
+
     /// Unsafe because it allows you to do things that are non-rusty.
     /// (Like raising an exception from anywhere, without having it
     /// be the return value)
@@ -132,15 +230,31 @@ impl NifError {
                 NifTerm::new(env, exception)
             },
             NifError::Atom(atom_str) => {
-                types::atom::get_atom_init(atom_str).to_term(env)
+                types::atom::make_atom_term(env, atom_str)
+            },
+            NifError::AtomString(atom_string) => {
+                match wrapper::atom::try_make_atom(env.as_c_arg(), &atom_string) {
+                    Ok(atom) => NifTerm::new(env, atom),
+                    Err(err) => err.encode(env),
+                }
             },
             NifError::RaiseAtom(atom_str) => {
-                let atom = types::atom::get_atom_init(atom_str).to_term(env);
+                let atom = types::atom::make_atom_term(env, atom_str);
                 let exception = wrapper::exception::raise_exception(
                     env.as_c_arg(),
                     atom.as_c_arg());
                 NifTerm::new(env, exception)
             },
+            NifError::RaiseAtomString(atom_string) => {
+                let atom = match wrapper::atom::try_make_atom(env.as_c_arg(), &atom_string) {
+                    Ok(atom) => atom,
+                    Err(err) => return err.encode(env),
+                };
+                let exception = wrapper::exception::raise_exception(
+                    env.as_c_arg(),
+                    atom);
+                NifTerm::new(env, exception)
+            },
             NifError::RaiseTerm(ref term_unencoded) => {
                 let term = term_unencoded.encode(env);
                 let exception = wrapper::exception::raise_exception(
@@ -148,6 +262,19 @@ impl NifError {
                     term.as_c_arg());
                 NifTerm::new(env, exception)
             },
+            NifError::Term(ref term_unencoded) => {
+                term_unencoded.encode(env)
+            },
+            NifError::Decode(ref decode_error) => {
+                match decode_error.mode() {
+                    decode_error::NifDecodeErrorMode::BadArg =>
+                        NifError::BadArg.encode(env),
+                    decode_error::NifDecodeErrorMode::ArgumentError => {
+                        let exception = exception::NifException::argument_error(decode_error.to_string());
+                        NifError::from(exception).encode(env)
+                    },
+                }
+            },
         }
     }
 
@@ -0,0 +1,24 @@
+//! Terms copied out of an environment into Rust-owned memory.
+
+/// A deep copy of a term, owned by Rust.
+///
+/// This is the `Term` of the `etf` module. Unlike a `SavedTerm`, an `OwnedTerm` doesn't belong to
+/// any environment. It can be kept for as long as needed, shared between threads, and encoded into
+/// any `NifEnv`, any number of times.
+///
+///     # use rustler::{ NifEnv, NifTerm, NifResult };
+///     use rustler::owned_term::OwnedTerm;
+///     use std::thread;
+///
+///     fn thread_example<'a>(env: NifEnv<'a>, term: NifTerm<'a>) -> NifResult<()> {
+///         let owned: OwnedTerm = try!(term.decode());
+///         thread::spawn(move || {
+///             // ... do stuff with owned, and encode it into another env later ...
+///         });
+///         Ok(())
+///     }
+///
+/// Terms without a variant of their own, like pids, references, funs, bitstrings and improper
+/// lists, and terms nested deeper than `etf::MAX_DEPTH`, are stored in the external term format
+/// as `OwnedTerm::Other`.
+pub type OwnedTerm = ::etf::Term;
@@ -0,0 +1,181 @@
+//! Handling of panics in NIFs.
+//!
+//! A panic in a NIF never unwinds into the BEAM. Instead, the NIF call fails in one of the ways
+//! given by `NifPanicMode`, which can be chosen for each NIF library with `set_panic_mode()`.
+//! Usually this is done in the load function:
+//!
+//! ```ignore
+//! fn on_load<'a>(_env: NifEnv<'a>, _load_info: NifTerm<'a>) -> bool {
+//!     rustler::panic::set_panic_mode(NifPanicMode::ErrorTuple);
+//!     true
+//! }
+//! ```
+//!
+//! The source location of a panic is recorded by a panic hook, which is installed when the library
+//! is loaded and removed again when it is unloaded. The hook only records panics in NIF calls, and
+//! passes every panic on to the hook that was installed before it.
+
+use std::any::Any;
+use std::cell::{ Cell, RefCell };
+use std::panic::{ self, UnwindSafe };
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use ::{ NifEnv, NifTerm, NifEncoder };
+use ::setting::{ LibrarySetting, SettingValue };
+use ::types::atom::{ self, make_atom_term };
+
+/// What a NIF call returns to Erlang when the NIF panics.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NifPanicMode {
+    /// Raise an `{nif_panic, Info}` error. `Info` is a map with the keys `message`, `file`,
+    /// `line`, `function` and `arity`. `message` is the panic message as a binary, `file` and
+    /// `line` are the source location of the panic, and `function` and `arity` identify the NIF.
+    ///
+    /// In Elixir, the error is raised as an `ErlangError`.
+    Raise,
+    /// Return `{error, {nif_panic, Message}}`, where `Message` is the panic message as a binary.
+    ErrorTuple,
+}
+
+impl SettingValue for NifPanicMode {
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => NifPanicMode::Raise,
+            _ => NifPanicMode::ErrorTuple,
+        }
+    }
+}
+
+static PANIC_MODE: LibrarySetting<NifPanicMode> = LibrarySetting::new();
+
+/// Sets the way all NIFs in this library fail when they panic. The default is
+/// `NifPanicMode::Raise`.
+pub fn set_panic_mode(mode: NifPanicMode) {
+    PANIC_MODE.set(mode);
+}
+
+/// Returns the current panic mode of this library.
+pub fn panic_mode() -> NifPanicMode {
+    PANIC_MODE.get()
+}
+
+thread_local! {
+    // Source location of the last panic in a NIF call on this thread. The payload caught by
+    // `catch_unwind` doesn't include it, so it's recorded by the panic hook.
+    static PANIC_LOCATION: RefCell<Option<(String, u32)>> = RefCell::new(None);
+
+    // Whether this thread is in a NIF call of this library, set by `catch_nif_panic()`.
+    static IN_NIF_CALL: Cell<bool> = Cell::new(false);
+}
+
+type PanicHook = Box<dyn Fn(&panic::PanicHookInfo) + Sync + Send + 'static>;
+
+// The hook that was installed before ours, restored when the library is unloaded.
+static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+static PREVIOUS_HOOK: Mutex<Option<PanicHook>> = Mutex::new(None);
+
+fn panic_hook(info: &panic::PanicHookInfo) {
+    if IN_NIF_CALL.with(|in_call| in_call.get()) {
+        let location = info.location().map(|loc| (loc.file().to_string(), loc.line()));
+        PANIC_LOCATION.with(|last| *last.borrow_mut() = location);
+    }
+    let previous = PREVIOUS_HOOK.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(ref previous) = *previous {
+        previous(info);
+    }
+}
+
+/// Installs a panic hook that records where panics in NIF calls happen, on top of the existing
+/// hook. Called when the library is loaded or upgraded.
+#[doc(hidden)]
+pub fn install_panic_hook() {
+    if HOOK_INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    // The hooks are swapped without holding the lock, which the hook itself takes while the
+    // standard library holds its own hook lock.
+    let previous = panic::take_hook();
+    *PREVIOUS_HOOK.lock().unwrap_or_else(|err| err.into_inner()) = Some(previous);
+    panic::set_hook(Box::new(panic_hook));
+}
+
+/// Puts back the panic hook that was installed before `install_panic_hook()`, so that no hook
+/// refers to the code of the library after it is unloaded. A hook set by the library itself after
+/// ours is replaced as well.
+#[doc(hidden)]
+pub fn uninstall_panic_hook() {
+    if !HOOK_INSTALLED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    drop(panic::take_hook());
+    let previous = PREVIOUS_HOOK.lock().unwrap_or_else(|err| err.into_inner()).take();
+    if let Some(previous) = previous {
+        panic::set_hook(previous);
+    }
+}
+
+/// Runs a NIF with panics caught, recording the location of a panic for `take_panic_location()`.
+#[doc(hidden)]
+pub fn catch_nif_panic<F, R>(function: F) -> ::std::thread::Result<R> where F: FnOnce() -> R + UnwindSafe {
+    let was_in_call = IN_NIF_CALL.with(|in_call| in_call.replace(true));
+    let result = panic::catch_unwind(function);
+    IN_NIF_CALL.with(|in_call| in_call.set(was_in_call));
+    result
+}
+
+/// Takes the source location of the last panic in a NIF call on this thread, if it was recorded.
+#[doc(hidden)]
+pub fn take_panic_location() -> Option<(String, u32)> {
+    PANIC_LOCATION.with(|last| last.borrow_mut().take())
+}
+
+/// Gets the message a panic was started with, if it was a string.
+#[doc(hidden)]
+pub fn panic_message(err: &Box<dyn Any + Send>) -> Option<&str> {
+    if let Some(string) = err.downcast_ref::<String>() {
+        Some(string)
+    } else if let Some(&s) = err.downcast_ref::<&'static str>() {
+        Some(s)
+    } else {
+        None
+    }
+}
+
+/// Encodes the message of a panic as a binary, or as the atom `nif_panic` if there is none.
+#[doc(hidden)]
+pub fn panic_reason<'a>(env: NifEnv<'a>, err: &Box<dyn Any + Send>) -> NifTerm<'a> {
+    match panic_message(err) {
+        Some(message) => message.encode(env),
+        None => atom::nif_panic().to_term(env),
+    }
+}
+
+/// Builds the term a NIF call that panicked returns or raises, according to the panic mode.
+/// Returns the term, and whether it should be raised.
+#[doc(hidden)]
+pub fn nif_panic_term<'a>(env: NifEnv<'a>, err: &Box<dyn Any + Send>, function: &'static str,
+                          arity: usize) -> (NifTerm<'a>, bool) {
+    let message = panic_message(err).unwrap_or("Box<Any>");
+    let location = take_panic_location();
+
+    match panic_mode() {
+        NifPanicMode::ErrorTuple => {
+            (term!(env, {:error, {:nif_panic, message}}), false)
+        },
+        NifPanicMode::Raise => {
+            let nil = atom::nil().to_term(env);
+            let (file, line) = match location {
+                Some((file, line)) => (file.encode(env), line.encode(env)),
+                None => (nil, nil),
+            };
+            let function = make_atom_term(env, function);
+            let arity = arity as u64;
+            (term!(env, {:nif_panic, %{message: message, file: file, line: line, function: function, arity: arity}}), true)
+        },
+    }
+}
@@ -0,0 +1,151 @@
+//! Private data of a NIF library.
+//!
+//! Every NIF library can have a single value of private data, which is set when the library is
+//! loaded and dropped when it is unloaded. It is a replacement for global state in `static mut`s
+//! or `lazy_static!`s, which would otherwise be shared between all versions of a library that are
+//! loaded during a hot code upgrade.
+//!
+//! The private data is set from the load function given to `rustler_export_nifs!`, and can be
+//! accessed from any NIF through its `NifEnv`:
+//!
+//! ```ignore
+//! struct Config {
+//!     pool_size: u32,
+//! }
+//!
+//! fn on_load<'a>(env: NifEnv<'a>, pool_size: u32) -> bool {
+//!     env.set_priv_data(Config { pool_size: pool_size })
+//! }
+//!
+//! fn pool_size<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+//!     let config: PrivData<Config> = env.priv_data().ok_or(NifError::BadArg)?;
+//!     Ok(config.pool_size.encode(env))
+//! }
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use ::NifEnv;
+use ::env::is_process_independent;
+use ::wrapper::nif_interface::{ self, c_void };
+
+type PrivDataBox = Box<Any + Send + Sync>;
+
+thread_local! {
+    // Private data set by the load function that is currently running on this thread, if any.
+    static LOADING: RefCell<Option<Option<PrivDataBox>>> = RefCell::new(None);
+}
+
+/// A reference to the private data of the NIF library, as returned by `NifEnv::priv_data()`.
+///
+/// The data is owned by the library and lives until the library is unloaded, which can not
+/// happen while a NIF call is running.
+pub struct PrivData<'a, T: 'a> {
+    data: &'a T,
+    env: PhantomData<NifEnv<'a>>,
+}
+
+impl<'a, T> Clone for PrivData<'a, T> {
+    fn clone(&self) -> Self {
+        PrivData {
+            data: self.data,
+            env: PhantomData,
+        }
+    }
+}
+impl<'a, T> Copy for PrivData<'a, T> {}
+
+impl<'a, T> Deref for PrivData<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.data
+    }
+}
+
+impl<'a> NifEnv<'a> {
+    /// Sets the private data of the NIF library, dropping the previous value if one was set
+    /// during the same load.
+    ///
+    /// This is only possible from the load or upgrade function given to `rustler_export_nifs!`.
+    /// Returns `false` if called anywhere else, so it can be used as the return value of the
+    /// load function.
+    pub fn set_priv_data<T>(self, data: T) -> bool
+        where T: Send + Sync + 'static
+    {
+        LOADING.with(|loading| {
+            match *loading.borrow_mut() {
+                Some(ref mut slot) => {
+                    *slot = Some(Box::new(data));
+                    true
+                },
+                None => false,
+            }
+        })
+    }
+
+    /// Returns the private data of the NIF library.
+    ///
+    /// Returns `None` if no private data was set when the library was loaded, if it is not of
+    /// type `T`, or if this is a process-independent environment (see `OwnedEnv`).
+    pub fn priv_data<T>(self) -> Option<PrivData<'a, T>>
+        where T: Send + Sync + 'static
+    {
+        // Process-independent environments are not associated with a NIF library, so
+        // `enif_priv_data` can't be called with them.
+        if is_process_independent(self) {
+            return None;
+        }
+
+        let ptr = unsafe { nif_interface::enif_priv_data(self.as_c_arg()) } as *const PrivDataBox;
+        if ptr.is_null() {
+            return None;
+        }
+        let data: &'a PrivDataBox = unsafe { &*ptr };
+        data.downcast_ref::<T>().map(|data| PrivData { data: data, env: PhantomData })
+    }
+}
+
+#[doc(hidden)]
+/// Runs a load or upgrade function, collecting the private data it sets.
+///
+/// If the function succeeds and sets private data, a pointer to it is written to `priv_data`,
+/// replacing any previous value. The pointer must later be passed to `drop_priv_data`.
+pub fn with_priv_data_slot<F, E>(priv_data: *mut *mut c_void, fun: F) -> Result<(), E>
+    where F: FnOnce() -> Result<(), E>
+{
+    // Closes the slot again even if `fun` panics.
+    struct LoadingGuard;
+    impl Drop for LoadingGuard {
+        fn drop(&mut self) {
+            LOADING.with(|loading| *loading.borrow_mut() = None);
+        }
+    }
+
+    LOADING.with(|loading| *loading.borrow_mut() = Some(None));
+    let _guard = LoadingGuard;
+    let result = fun();
+    let data = LOADING.with(|loading| loading.borrow_mut().take()).and_then(|slot| slot);
+
+    if result.is_ok() {
+        if let Some(data) = data {
+            // On reload, the private data from the previous load is replaced.
+            unsafe {
+                drop_priv_data(*priv_data);
+                *priv_data = Box::into_raw(Box::new(data)) as *mut c_void;
+            }
+        }
+    }
+    result
+}
+
+#[doc(hidden)]
+/// Drops the private data written to `priv_data` by `with_priv_data_slot`.
+pub unsafe fn drop_priv_data(priv_data: *mut c_void) {
+    if !priv_data.is_null() {
+        drop(Box::from_raw(priv_data as *mut PrivDataBox));
+    }
+}
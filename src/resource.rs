@@ -2,6 +2,12 @@
 //! A NIF resource allows you to safely store rust structs in a term, and therefore keep it across
 //! NIF calls. The struct will be automatically dropped when the BEAM GC decides that there are no
 //! more references to the resource.
+//!
+//! Resource types are opened with takeover semantics. When a new version of a NIF library is
+//! loaded during a hot code upgrade, it takes over all instances of its resource types that were
+//! created by the old version, and the new version's destructor will be the one called when they
+//! are dropped. For this to be sound, the struct stored in a resource must keep the same memory
+//! layout across upgrades.
 
 use std::mem;
 use std::ptr;
@@ -9,10 +15,10 @@ use std::ops::Deref;
 use std::marker::PhantomData;
 
 use super::{ NifTerm, NifEnv, NifError, NifEncoder, NifDecoder, NifResult };
-use ::wrapper::nif_interface::{ NIF_RESOURCE_TYPE, MUTABLE_NIF_RESOURCE_HANDLE, NIF_ENV, NifResourceFlags };
+use ::wrapper::nif_interface::{ NIF_RESOURCE_TYPE, MUTABLE_NIF_RESOURCE_HANDLE, NIF_ENV };
 use ::wrapper::nif_interface::{ c_void };
 
-/// Re-export a type used by the `resource_struct_init!` macro.
+/// The flags a resource type can be opened with.
 pub use ::wrapper::nif_interface::NIF_RESOURCE_FLAGS;
 
 /// The NifResourceType struct contains a  NIF_RESOURCE_TYPE and a phantom reference to the type it
@@ -50,13 +56,32 @@ impl<'a, T> NifDecoder<'a> for ResourceCell<T> where T: NifResourceTypeProvider
     }
 }
 
+/// The value `resource_struct_init!` returns from the enclosing load function when the resource
+/// type can't be opened. Implemented for both return types a load function can have.
+#[doc(hidden)]
+pub trait NifResourceInitFailure {
+    fn resource_init_failure() -> Self;
+}
+
+impl NifResourceInitFailure for bool {
+    fn resource_init_failure() -> Self {
+        false
+    }
+}
+
+impl NifResourceInitFailure for NifResult<()> {
+    fn resource_init_failure() -> Self {
+        Err(NifError::Atom("resource_type_init_failed"))
+    }
+}
+
 /// This is the function that gets called from resource_struct_init! in on_load to create a new
-/// resource type.
+/// resource type, or to take over the instances of the previous version of the library.
 #[doc(hidden)]
-pub fn open_struct_resource_type<'a, T: NifResourceTypeProvider>(env: NifEnv<'a>, name: &str,
-                                 flags: NifResourceFlags) -> Option<NifResourceType<T>> {
+pub fn open_struct_resource_type<'a, T: NifResourceTypeProvider>(env: NifEnv<'a>, name: &str)
+                                 -> Option<NifResourceType<T>> {
     let res: Option<NIF_RESOURCE_TYPE> = unsafe {
-        ::wrapper::resource::open_resource_type(env.as_c_arg(), name, Some(T::destructor), flags)
+        ::wrapper::resource::open_or_take_over_resource_type(env.as_c_arg(), name, Some(T::destructor))
     };
     if res.is_some() {
         Some(NifResourceType {
@@ -106,7 +131,7 @@ impl<T> ResourceCell<T> where T: NifResourceTypeProvider + Sync {
     fn from_term(term: NifTerm) -> Result<Self, NifError> {
         let res_resource = match unsafe { ::wrapper::resource::get_resource(term.get_env().as_c_arg(), term.as_c_arg(), T::get_type().res) } {
             Some(res) => res,
-            None => return Err(NifError::BadArg),
+            None => return Err(NifError::expected("resource")),
         };
         unsafe { ::wrapper::resource::keep_resource(res_resource); }
         let casted_ptr = unsafe { align_alloced_mem_for_struct::<Box<T>>(res_resource) } as *mut Box<T>;
@@ -164,17 +189,15 @@ macro_rules! resource_struct_init {
         {
             static mut STRUCT_TYPE: Option<$crate::resource::NifResourceType<$struct_name>> = None;
 
+            // Take over the instances left behind by a previous version of the library if there
+            // is one (hot code upgrade), otherwise create the resource type.
             let temp_struct_type =
                 match $crate::resource::open_struct_resource_type::<$struct_name>(
                     $env,
-                    stringify!($struct_name),
-                    $crate::resource::NIF_RESOURCE_FLAGS::ERL_NIF_RT_CREATE
+                    stringify!($struct_name)
                     ) {
                     Some(inner) => inner,
-                    None => {
-                        println!("Failure in creating resource type");
-                        return false;
-                    }
+                    None => return $crate::resource::NifResourceInitFailure::resource_init_failure(),
                 };
             unsafe { STRUCT_TYPE = Some(temp_struct_type) };
 
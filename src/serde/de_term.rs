@@ -0,0 +1,362 @@
+use std::str;
+use std::vec;
+
+use serde_crate::de::{ self, Visitor, DeserializeSeed };
+
+use ::NifTerm;
+use ::dynamic::TermType;
+use ::types::atom;
+use ::types::binary::NifBinary;
+use ::types::list::NifListIterator;
+use ::types::map::NifMapIterator;
+use ::types::tuple::{ get_tuple, get_tuple_ref, NifTupleRef };
+use super::{ Error, ELIXIR_STRUCT_PREFIX };
+
+/// Deserializes values from a term.
+#[derive(Clone, Copy)]
+pub struct Deserializer<'a> {
+    term: NifTerm<'a>,
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new(term: NifTerm<'a>) -> Self {
+        Deserializer { term: term }
+    }
+
+    fn is_nil(&self) -> bool {
+        self.term == atom::nil().to_term(self.term.get_env())
+    }
+
+    fn binary(&self) -> Option<&'a [u8]> {
+        NifBinary::from_term(self.term).ok().map(|binary| binary.as_slice())
+    }
+
+    fn tuple(&self, len: usize) -> Result<NifTupleRef<'a>, Error> {
+        match get_tuple_ref(self.term) {
+            Ok(items) if items.len() == len => Ok(items),
+            _ => Err(Error::expected(&format!("tuple of size {}", len))),
+        }
+    }
+}
+
+macro_rules! deserialize_number {
+    ($deserialize:ident, $visit:ident, $typ:ty) => {
+        fn $deserialize<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.term.decode::<$typ>() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(Error::expected(stringify!($typ))),
+            }
+        }
+    }
+}
+
+impl<'a> de::Deserializer<'a> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.term.get_type() {
+            TermType::Atom => {
+                if let Ok(value) = self.term.decode::<bool>() {
+                    visitor.visit_bool(value)
+                } else if self.is_nil() {
+                    visitor.visit_unit()
+                } else {
+                    self.deserialize_string(visitor)
+                }
+            },
+            TermType::Integer => {
+                if let Ok(value) = self.term.decode::<i64>() {
+                    visitor.visit_i64(value)
+                } else {
+                    self.deserialize_u64(visitor)
+                }
+            },
+            TermType::Float =>
+                self.deserialize_f64(visitor),
+            TermType::Binary => {
+                let bytes = self.binary().unwrap();
+                match str::from_utf8(bytes) {
+                    Ok(string) => visitor.visit_borrowed_str(string),
+                    Err(_) => visitor.visit_borrowed_bytes(bytes),
+                }
+            },
+            TermType::EmptyList | TermType::List =>
+                self.deserialize_seq(visitor),
+            TermType::Tuple => {
+                let items = get_tuple(self.term).ok().unwrap();
+                visitor.visit_seq(SequenceAccess::new(items.into_iter()))
+            },
+            TermType::Map =>
+                self.deserialize_map(visitor),
+            other =>
+                Err(de::Error::custom(format!("can't deserialize a term of type {:?}", other))),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.term.decode() {
+            Ok(value) => visitor.visit_bool(value),
+            Err(_) => Err(Error::expected("boolean")),
+        }
+    }
+
+    deserialize_number!(deserialize_i8, visit_i8, i8);
+    deserialize_number!(deserialize_i16, visit_i16, i16);
+    deserialize_number!(deserialize_i32, visit_i32, i32);
+    deserialize_number!(deserialize_i64, visit_i64, i64);
+    deserialize_number!(deserialize_u8, visit_u8, u8);
+    deserialize_number!(deserialize_u16, visit_u16, u16);
+    deserialize_number!(deserialize_u32, visit_u32, u32);
+    deserialize_number!(deserialize_u64, visit_u64, u64);
+    deserialize_number!(deserialize_f32, visit_f32, f32);
+    deserialize_number!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_char<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        let string = self.binary().and_then(|bytes| str::from_utf8(bytes).ok());
+        let mut chars = string.unwrap_or("").chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::expected("UTF-8 binary of one character")),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        if let Some(bytes) = self.binary() {
+            match str::from_utf8(bytes) {
+                Ok(string) => return visitor.visit_borrowed_str(string),
+                Err(_) => return Err(Error::expected("UTF-8 binary or atom")),
+            }
+        }
+        match self.term.atom_to_string() {
+            Ok(name) => visitor.visit_string(name),
+            Err(_) => Err(Error::expected("UTF-8 binary or atom")),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.binary() {
+            Some(bytes) => visitor.visit_borrowed_bytes(bytes),
+            None => Err(Error::expected("binary")),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_nil() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_nil() {
+            visitor.visit_unit()
+        } else {
+            Err(Error::expected("nil"))
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'a>>(self, _name: &'static str, visitor: V)
+                                               -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'a>>(self, _name: &'static str, visitor: V)
+                                                  -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.term.decode::<NifListIterator>() {
+            Ok(iter) => visitor.visit_seq(SequenceAccess::new(iter)),
+            Err(_) if self.term.is_list() => Err(Error::expected("proper list")),
+            Err(_) => Err(Error::expected("list")),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'a>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        let items = try!(self.tuple(len));
+        visitor.visit_seq(SequenceAccess::new(items.into_iter()))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'a>>(self, _name: &'static str, len: usize, visitor: V)
+                                                -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match NifMapIterator::new(self.term) {
+            Some(iter) => visitor.visit_map(MapAccess::new(iter, false)),
+            None => Err(Error::expected("map")),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'a>>(self, name: &'static str, _fields: &'static [&'static str],
+                                          visitor: V) -> Result<V::Value, Error> {
+        let iter = match NifMapIterator::new(self.term) {
+            Some(iter) => iter,
+            None => return Err(Error::expected("map")),
+        };
+        if name.starts_with(ELIXIR_STRUCT_PREFIX) {
+            let key = atom::__struct__().to_term(self.term.get_env());
+            let module = self.term.map_get(key).and_then(|module| module.atom_to_string());
+            match module {
+                Ok(ref module) if module == name => (),
+                _ => return Err(Error::expected(&format!("%{}{{}}", &name[ELIXIR_STRUCT_PREFIX.len()..]))),
+            }
+        }
+        visitor.visit_map(MapAccess::new(iter, true))
+    }
+
+    fn deserialize_enum<V: Visitor<'a>>(self, _name: &'static str, _variants: &'static [&'static str],
+                                        visitor: V) -> Result<V::Value, Error> {
+        if self.term.is_atom() {
+            return visitor.visit_enum(EnumAccess { tag: self.term, rest: Vec::new() });
+        }
+        if let Ok(mut items) = get_tuple(self.term) {
+            if items.len() >= 2 && items[0].is_atom() {
+                let tag = items.remove(0);
+                return visitor.visit_enum(EnumAccess { tag: tag, rest: items });
+            }
+        }
+        Err(Error::expected("atom or tuple tagged with an atom"))
+    }
+
+    fn deserialize_identifier<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// Deserializes the items of lists and tuples.
+struct SequenceAccess<I> {
+    iter: I,
+}
+
+impl<'a, I> SequenceAccess<I> where I: Iterator<Item = NifTerm<'a>> {
+    fn new(iter: I) -> Self {
+        SequenceAccess { iter: iter }
+    }
+}
+
+impl<'a, I> de::SeqAccess<'a> for SequenceAccess<I> where I: Iterator<Item = NifTerm<'a>> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'a>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.iter.next() {
+            Some(term) => seed.deserialize(Deserializer::new(term)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Deserializes the entries of maps. For structs, the `__struct__` key is skipped.
+struct MapAccess<'a> {
+    iter: NifMapIterator<'a>,
+    value: Option<NifTerm<'a>>,
+    skip_struct_key: bool,
+}
+
+impl<'a> MapAccess<'a> {
+    fn new(iter: NifMapIterator<'a>, skip_struct_key: bool) -> Self {
+        MapAccess {
+            iter: iter,
+            value: None,
+            skip_struct_key: skip_struct_key,
+        }
+    }
+}
+
+impl<'a> de::MapAccess<'a> for MapAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'a>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        while let Some((key, value)) = self.iter.next() {
+            if self.skip_struct_key && key == atom::__struct__().to_term(key.get_env()) {
+                continue;
+            }
+            self.value = Some(value);
+            return seed.deserialize(Deserializer::new(key)).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'a>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Deserializer::new(value)),
+            None => Err(de::Error::custom("map value deserialized before its key")),
+        }
+    }
+}
+
+/// Deserializes an enum from an atom, or a tuple tagged with an atom.
+struct EnumAccess<'a> {
+    tag: NifTerm<'a>,
+    rest: Vec<NifTerm<'a>>,
+}
+
+impl<'a> de::EnumAccess<'a> for EnumAccess<'a> {
+    type Error = Error;
+    type Variant = VariantAccess<'a>;
+
+    fn variant_seed<V: DeserializeSeed<'a>>(self, seed: V) -> Result<(V::Value, VariantAccess<'a>), Error> {
+        let variant = try!(seed.deserialize(Deserializer::new(self.tag)));
+        Ok((variant, VariantAccess { rest: self.rest }))
+    }
+}
+
+struct VariantAccess<'a> {
+    rest: Vec<NifTerm<'a>>,
+}
+
+impl<'a> VariantAccess<'a> {
+    fn single(self, expected: &str) -> Result<NifTerm<'a>, Error> {
+        if self.rest.len() == 1 {
+            Ok(self.rest[0])
+        } else {
+            Err(Error::expected(expected))
+        }
+    }
+}
+
+impl<'a> de::VariantAccess<'a> for VariantAccess<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        if self.rest.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::expected("atom"))
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'a>>(self, seed: T) -> Result<T::Value, Error> {
+        let value = try!(self.single("tuple of size 2"));
+        seed.deserialize(Deserializer::new(value))
+    }
+
+    fn tuple_variant<V: Visitor<'a>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        if self.rest.len() != len {
+            return Err(Error::expected(&format!("tuple of size {}", len + 1)));
+        }
+        let iter: vec::IntoIter<NifTerm<'a>> = self.rest.into_iter();
+        visitor.visit_seq(SequenceAccess::new(iter))
+    }
+
+    fn struct_variant<V: Visitor<'a>>(self, _fields: &'static [&'static str], visitor: V)
+                                      -> Result<V::Value, Error> {
+        let value = try!(self.single("tuple of size 2"));
+        de::Deserializer::deserialize_map(Deserializer::new(value), visitor)
+    }
+}
@@ -0,0 +1,149 @@
+//! Conversion between terms and Rust values implementing `serde::Serialize` and
+//! `serde::Deserialize`. Requires the `serde` feature.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! #[serde(rename = "Elixir.MyApp.Point")]
+//! struct Point { x: i64, y: i64 }
+//!
+//! fn flip<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+//!     let point: Point = rustler::serde::from_term(args[0])?;
+//!     Ok(rustler::serde::to_term(env, &Point { x: point.y, y: point.x })?)
+//! }
+//! ```
+//!
+//! `SerdeTerm<T>` wraps a value to use it where a `NifEncoder` or `NifDecoder` is expected.
+//!
+//! # Mapping
+//!
+//! | Rust                                 | Term                                    |
+//! |--------------------------------------|-----------------------------------------|
+//! | `bool`                               | `true` or `false`                       |
+//! | integers, floats                     | integers, floats                        |
+//! | `char`, `&str`, `String`             | UTF-8 binary                            |
+//! | bytes (`serde_bytes`)                | binary                                  |
+//! | `None`, `()`, unit struct            | `nil`                                   |
+//! | `Some(value)`, newtype struct        | `value`                                 |
+//! | sequence, like `Vec<T>`              | list                                    |
+//! | tuple, tuple struct                  | tuple                                   |
+//! | map, like `HashMap<K, V>`            | map                                     |
+//! | struct                               | map with atom keys, or Elixir struct    |
+//! | unit variant `E::A`                  | atom `A`                                |
+//! | newtype variant `E::A(v)`            | `{A, v}`                                |
+//! | tuple variant `E::A(v, w)`           | `{A, v, w}`                             |
+//! | struct variant `E::A { f: v }`       | `{A, %{f: v}}`                          |
+//!
+//! Atoms are made from the names in Rust code as they are, so use serde's `rename` and
+//! `rename_all` attributes to get the usual atom names, like `#[serde(rename_all = "snake_case")]`
+//! for enums.
+//!
+//! A struct is an Elixir struct when its name starts with `Elixir.`, like
+//! `#[serde(rename = "Elixir.MyApp.Point")]`. Deserializing such a struct requires the
+//! `__struct__` key to name the same module. The `__struct__` key of other maps is ignored when
+//! deserializing a struct.
+//!
+//! When deserializing, strings and identifiers are read from both binaries and atoms, so maps with
+//! atom keys can be deserialized into `HashMap<String, V>`. Strings and bytes borrow from the
+//! binary, so `&'a str` and `&'a [u8]` can be deserialized without copying.
+
+use std::error;
+use std::fmt;
+
+use serde_crate::{ ser, de };
+
+use ::{ NifEnv, NifTerm, NifError, NifResult, NifEncoder, NifDecoder };
+use ::exception::NifException;
+
+mod ser_term;
+mod de_term;
+
+pub use self::ser_term::Serializer;
+pub use self::de_term::Deserializer;
+
+/// Serializes a value into a term.
+pub fn to_term<'a, T: ?Sized>(env: NifEnv<'a>, value: &T) -> Result<NifTerm<'a>, Error>
+    where T: ser::Serialize
+{
+    value.serialize(Serializer::new(env))
+}
+
+/// Deserializes a term into a value.
+pub fn from_term<'a, T>(term: NifTerm<'a>) -> Result<T, Error>
+    where T: de::Deserialize<'a>
+{
+    T::deserialize(Deserializer::new(term))
+}
+
+/// Encodes and decodes a value with serde.
+///
+/// # Errors
+///
+/// Decoding raises an `ArgumentError` with the message of the deserialization error.
+///
+/// # Panics
+///
+/// Encoding panics if the value can't be serialized.
+pub struct SerdeTerm<T>(pub T);
+
+impl<T> NifEncoder for SerdeTerm<T> where T: ser::Serialize {
+    fn encode<'a>(&self, env: NifEnv<'a>) -> NifTerm<'a> {
+        match to_term(env, &self.0) {
+            Ok(term) => term,
+            Err(err) => panic!("serialization failed: {}", err),
+        }
+    }
+}
+
+impl<'a, T> NifDecoder<'a> for SerdeTerm<T> where T: de::Deserialize<'a> + 'a {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        match from_term(term) {
+            Ok(value) => Ok(SerdeTerm(value)),
+            Err(err) => Err(NifException::argument_error(err.to_string()).into()),
+        }
+    }
+}
+
+/// A serialization or deserialization error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    message: String,
+}
+
+impl Error {
+    fn expected(what: &str) -> Error {
+        Error { message: format!("expected {}", what) }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<Error> for NifError {
+    fn from(err: Error) -> NifError {
+        NifError::from_error(err)
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error { message: msg.to_string() }
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error { message: msg.to_string() }
+    }
+}
+
+// The prefix of the names of structs that are Elixir structs.
+const ELIXIR_STRUCT_PREFIX: &'static str = "Elixir.";
@@ -0,0 +1,353 @@
+use serde_crate::ser::{ self, Serialize };
+
+use ::{ NifEnv, NifTerm, NifEncoder };
+use ::types::atom::{ self, NifAtom };
+use ::types::map::make_map_from_pairs;
+use ::types::tuple::make_tuple;
+use super::{ Error, ELIXIR_STRUCT_PREFIX };
+
+/// Serializes values into terms in an environment.
+#[derive(Clone, Copy)]
+pub struct Serializer<'a> {
+    env: NifEnv<'a>,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(env: NifEnv<'a>) -> Self {
+        Serializer { env: env }
+    }
+
+    fn atom(&self, name: &str) -> Result<NifTerm<'a>, Error> {
+        match NifAtom::from_str(self.env, name) {
+            Ok(atom) => Ok(atom.to_term(self.env)),
+            Err(_) => Err(ser::Error::custom(format!("can't create atom {:?}", name))),
+        }
+    }
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    type SerializeSeq = SequenceSerializer<'a>;
+    type SerializeTuple = SequenceSerializer<'a>;
+    type SerializeTupleStruct = SequenceSerializer<'a>;
+    type SerializeTupleVariant = SequenceSerializer<'a>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = MapSerializer<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Error> {
+        Ok(v.encode(self.env))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Error> {
+        Ok(v.encode(self.env))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Error> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Error> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Error> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Error> {
+        Ok(v.encode(self.env))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Error> {
+        Ok(v.encode(self.env))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Error> {
+        Ok(v.to_string().encode(self.env))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Error> {
+        Ok(v.encode(self.env))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Error> {
+        let mut binary = match ::types::binary::OwnedNifBinary::alloc(v.len()) {
+            Some(binary) => binary,
+            None => return Err(ser::Error::custom("binary term allocation fail")),
+        };
+        binary.as_mut_slice().copy_from_slice(v);
+        Ok(binary.release(self.env).get_term(self.env))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(atom::nil().to_term(self.env))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Ok(atom::nil().to_term(self.env))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str)
+                              -> Result<Self::Ok, Error> {
+        self.atom(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T)
+                                                       -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _variant_index: u32,
+                                                        variant: &'static str, value: &T)
+                                                        -> Result<Self::Ok, Error> {
+        let tag = try!(self.atom(variant));
+        let value = try!(value.serialize(self));
+        Ok(make_tuple(self.env, &[tag, value]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SequenceSerializer<'a>, Error> {
+        Ok(SequenceSerializer::new(self, len.unwrap_or(0), SequenceKind::List))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SequenceSerializer<'a>, Error> {
+        Ok(SequenceSerializer::new(self, len, SequenceKind::Tuple))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SequenceSerializer<'a>, Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str,
+                               len: usize) -> Result<SequenceSerializer<'a>, Error> {
+        let mut serializer = SequenceSerializer::new(self, len + 1, SequenceKind::Tuple);
+        serializer.items.push(try!(self.atom(variant)));
+        Ok(serializer)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'a>, Error> {
+        Ok(MapSerializer::new(self, None))
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<MapSerializer<'a>, Error> {
+        let mut serializer = MapSerializer::new(self, None);
+        if name.starts_with(ELIXIR_STRUCT_PREFIX) {
+            let key = atom::__struct__().to_term(self.env);
+            let module = try!(self.atom(name));
+            serializer.put(key, module);
+        }
+        Ok(serializer)
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str,
+                                _len: usize) -> Result<MapSerializer<'a>, Error> {
+        let tag = try!(self.atom(variant));
+        Ok(MapSerializer::new(self, Some(tag)))
+    }
+}
+
+#[doc(hidden)]
+pub enum SequenceKind {
+    List,
+    Tuple,
+}
+
+/// Serializes sequences into lists, and tuples and tuple variants into tuples.
+#[doc(hidden)]
+pub struct SequenceSerializer<'a> {
+    serializer: Serializer<'a>,
+    items: Vec<NifTerm<'a>>,
+    kind: SequenceKind,
+}
+
+impl<'a> SequenceSerializer<'a> {
+    fn new(serializer: Serializer<'a>, len: usize, kind: SequenceKind) -> Self {
+        SequenceSerializer {
+            serializer: serializer,
+            items: Vec::with_capacity(len),
+            kind: kind,
+        }
+    }
+
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let term = try!(value.serialize(self.serializer));
+        self.items.push(term);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<NifTerm<'a>, Error> {
+        let env = self.serializer.env;
+        match self.kind {
+            SequenceKind::List => Ok(self.items.encode(env)),
+            SequenceKind::Tuple => Ok(make_tuple(env, &self.items)),
+        }
+    }
+}
+
+impl<'a> ser::SerializeSeq for SequenceSerializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<NifTerm<'a>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for SequenceSerializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<NifTerm<'a>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for SequenceSerializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<NifTerm<'a>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for SequenceSerializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<NifTerm<'a>, Error> {
+        self.finish()
+    }
+}
+
+/// Serializes maps, structs and struct variants into maps. Struct variants are wrapped in a tuple
+/// with the variant name.
+#[doc(hidden)]
+pub struct MapSerializer<'a> {
+    serializer: Serializer<'a>,
+    pairs: Vec<(NifTerm<'a>, NifTerm<'a>)>,
+    key: Option<NifTerm<'a>>,
+    variant: Option<NifTerm<'a>>,
+}
+
+impl<'a> MapSerializer<'a> {
+    fn new(serializer: Serializer<'a>, variant: Option<NifTerm<'a>>) -> Self {
+        MapSerializer {
+            serializer: serializer,
+            pairs: Vec::new(),
+            key: None,
+            variant: variant,
+        }
+    }
+
+    fn put(&mut self, key: NifTerm<'a>, value: NifTerm<'a>) {
+        self.pairs.push((key, value));
+    }
+
+    fn put_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        let key = try!(self.serializer.atom(key));
+        let value = try!(value.serialize(self.serializer));
+        self.put(key, value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<NifTerm<'a>, Error> {
+        let map = make_map_from_pairs(self.serializer.env, &self.pairs);
+        match self.variant {
+            Some(tag) => Ok(make_tuple(self.serializer.env, &[tag, map])),
+            None => Ok(map),
+        }
+    }
+}
+
+impl<'a> ser::SerializeMap for MapSerializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(try!(key.serialize(self.serializer)));
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => return Err(ser::Error::custom("map value serialized before its key")),
+        };
+        let value = try!(value.serialize(self.serializer));
+        self.put(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<NifTerm<'a>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStruct for MapSerializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.put_field(key, value)
+    }
+
+    fn end(self) -> Result<NifTerm<'a>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for MapSerializer<'a> {
+    type Ok = NifTerm<'a>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.put_field(key, value)
+    }
+
+    fn end(self) -> Result<NifTerm<'a>, Error> {
+        self.finish()
+    }
+}
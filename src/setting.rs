@@ -0,0 +1,38 @@
+//! Settings that apply to all NIFs of a library, like the panic mode.
+//!
+//! Every NIF library has its own copy of this crate, and therefore its own copy of the statics
+//! holding these settings. Libraries loaded at the same time don't see each other's settings.
+
+use std::marker::PhantomData;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+/// A value that can be kept in a `LibrarySetting`. The value with index 0 is the default.
+pub trait SettingValue: Copy {
+    fn to_index(self) -> usize;
+    fn from_index(index: usize) -> Self;
+}
+
+/// A setting of this NIF library, kept in a static.
+pub struct LibrarySetting<T> {
+    index: AtomicUsize,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> LibrarySetting<T> {
+    pub const fn new() -> Self {
+        LibrarySetting {
+            index: AtomicUsize::new(0),
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T: SettingValue> LibrarySetting<T> {
+    pub fn get(&self) -> T {
+        T::from_index(self.index.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, value: T) {
+        self.index.store(value.to_index(), Ordering::Relaxed);
+    }
+}
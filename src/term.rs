@@ -1,5 +1,9 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
 use ::{NifDecoder, NifEnv, NifResult};
 use ::wrapper::nif_interface::{NIF_TERM};
+use ::wrapper::term;
 
 /// NifTerm is used to represent all erlang terms. Terms are always lifetime limited by a NifEnv.
 ///
@@ -61,6 +65,49 @@ impl<'a> NifTerm<'a> {
     /// let number: i32 = try!(term.decode());
     /// ```
     pub fn decode<T>(self) -> NifResult<T> where T: NifDecoder<'a> {
-        NifDecoder::decode(self)
+        NifDecoder::decode(self).map_err(|err| err.decoded_from(self.term))
+    }
+}
+
+/// Terms are equal when they are exactly equal, like with `=:=`. `1` and `1.0` are not equal.
+impl<'a, 'b> PartialEq<NifTerm<'b>> for NifTerm<'a> {
+    fn eq(&self, other: &NifTerm<'b>) -> bool {
+        unsafe { term::is_identical(self.as_c_arg(), other.as_c_arg()) }
+    }
+}
+
+impl<'a> Eq for NifTerm<'a> {}
+
+impl<'a, 'b> PartialOrd<NifTerm<'b>> for NifTerm<'a> {
+    fn partial_cmp(&self, other: &NifTerm<'b>) -> Option<Ordering> {
+        Some(cmp_terms(self, other))
+    }
+}
+
+/// Terms are ordered in Erlang term order. Terms that are equal in Erlang but not exactly equal,
+/// like `1` and `1.0`, are ordered consistently, but in no particular order.
+impl<'a> Ord for NifTerm<'a> {
+    fn cmp(&self, other: &NifTerm<'a>) -> Ordering {
+        cmp_terms(self, other)
+    }
+}
+
+fn cmp_terms(lhs: &NifTerm, rhs: &NifTerm) -> Ordering {
+    match unsafe { term::compare(lhs.as_c_arg(), rhs.as_c_arg()) } {
+        Ordering::Equal if lhs != rhs => {
+            // Keep the order consistent with `Eq` by comparing the encoded terms, which differ
+            // for terms that are not exactly equal.
+            let lhs_bytes = unsafe { term::term_to_bytes(lhs.get_env().as_c_arg(), lhs.as_c_arg()) };
+            let rhs_bytes = unsafe { term::term_to_bytes(rhs.get_env().as_c_arg(), rhs.as_c_arg()) };
+            lhs_bytes.cmp(&rhs_bytes)
+        },
+        ordering => ordering,
+    }
+}
+
+/// Terms are hashed with the portable hash of the VM, `erlang:phash2/1`.
+impl<'a> Hash for NifTerm<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(unsafe { term::phash2(self.as_c_arg()) });
     }
 }
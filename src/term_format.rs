@@ -0,0 +1,473 @@
+//! Formatting of terms in Elixir or Erlang syntax.
+//!
+//! `NifTerm` implements `Display` and `Debug`, so terms can be printed like any other Rust value:
+//!
+//! ```ignore
+//! println!("{}", term);   // %{a: [1, 2]}
+//! ```
+//!
+//! Both use the syntax set with `set_syntax()`, which is Elixir by default. `Debug` limits the
+//! output like `inspect/2` does, showing at most 10 levels of nested terms and 50 items of each
+//! list, tuple, map or binary. `Display` shows the entire term. `NifTerm::format()` gives control
+//! over the syntax and limits of a single term:
+//!
+//! ```ignore
+//! let formatted = term.format().syntax(TermSyntax::Erlang).max_items(3).to_string();
+//! // #{a => [1,2,3,...]}
+//! ```
+//!
+//! Pids, ports, references and funs are shown without their contents, like `#PID<...>`.
+
+use std::fmt::{ self, Write };
+use std::str;
+
+use ::NifTerm;
+use ::dynamic::TermType;
+use ::etf;
+use ::setting::{ LibrarySetting, SettingValue };
+use ::types::atom;
+use ::types::binary::NifBinary;
+use ::types::map::NifMapIterator;
+use ::types::tuple::get_tuple_ref;
+
+/// The syntax terms are formatted in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TermSyntax {
+    /// `%{a: [1, 2], "b" => {:ok, "c"}}`
+    Elixir,
+    /// `#{a => [1,2], <<"b">> => {ok,<<"c">>}}`
+    Erlang,
+}
+
+impl SettingValue for TermSyntax {
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => TermSyntax::Elixir,
+            _ => TermSyntax::Erlang,
+        }
+    }
+}
+
+// The syntax of `Display` and `Debug`.
+static SYNTAX: LibrarySetting<TermSyntax> = LibrarySetting::new();
+
+/// Sets the syntax that `Display` and `Debug` format terms in, for all NIFs in this library. The
+/// default is `TermSyntax::Elixir`.
+pub fn set_syntax(syntax: TermSyntax) {
+    SYNTAX.set(syntax);
+}
+
+/// Returns the syntax that `Display` and `Debug` format terms in.
+pub fn syntax() -> TermSyntax {
+    SYNTAX.get()
+}
+
+const DEBUG_MAX_DEPTH: usize = 10;
+const DEBUG_MAX_ITEMS: usize = 50;
+
+/// A term with formatting options, implementing `Display`. Created with `NifTerm::format()`.
+#[derive(Clone, Copy)]
+pub struct TermFormat<'a> {
+    term: NifTerm<'a>,
+    syntax: TermSyntax,
+    max_depth: Option<usize>,
+    max_items: Option<usize>,
+}
+
+impl<'a> TermFormat<'a> {
+    pub fn syntax(mut self, syntax: TermSyntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// Shows terms nested deeper than `max_depth` as `...`.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Shows only the first `max_items` items of lists, tuples and maps, and bytes of binaries.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+}
+
+/// ## Formatting
+impl<'a> NifTerm<'a> {
+
+    /// Returns the term with options for formatting it. Without setting any options, it is
+    /// formatted like with `Display`.
+    pub fn format(self) -> TermFormat<'a> {
+        TermFormat {
+            term: self,
+            syntax: syntax(),
+            max_depth: None,
+            max_items: None,
+        }
+    }
+
+}
+
+impl<'a> fmt::Display for TermFormat<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Formatter { f: f, options: self }.term(self.term, 0)
+    }
+}
+
+impl<'a> fmt::Display for NifTerm<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.format(), f)
+    }
+}
+
+impl<'a> fmt::Debug for NifTerm<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.format().max_depth(DEBUG_MAX_DEPTH).max_items(DEBUG_MAX_ITEMS), f)
+    }
+}
+
+struct Formatter<'f, 'g: 'f, 'o, 'a: 'o> {
+    f: &'f mut fmt::Formatter<'g>,
+    options: &'o TermFormat<'a>,
+}
+
+impl<'f, 'g, 'o, 'a> Formatter<'f, 'g, 'o, 'a> {
+    fn elixir(&self) -> bool {
+        self.options.syntax == TermSyntax::Elixir
+    }
+
+    fn separator(&self) -> &'static str {
+        if self.elixir() { ", " } else { "," }
+    }
+
+    fn too_deep(&self, depth: usize) -> bool {
+        self.options.max_depth.map_or(false, |max_depth| depth >= max_depth)
+    }
+
+    fn too_many(&self, count: usize) -> bool {
+        self.options.max_items.map_or(false, |max_items| count >= max_items)
+    }
+
+    fn term(&mut self, term: NifTerm, depth: usize) -> fmt::Result {
+        match term.get_type() {
+            TermType::Atom =>
+                self.atom(&term.atom_to_string().ok().unwrap()),
+            TermType::Integer =>
+                self.integer(term),
+            TermType::Float => {
+                let value: f64 = term.decode().ok().unwrap();
+                self.float(value)
+            },
+            TermType::Binary =>
+                self.binary(NifBinary::from_term(term).ok().unwrap().as_slice()),
+            TermType::Bitstring =>
+                self.bitstring(term),
+            TermType::EmptyList =>
+                self.f.write_str("[]"),
+            TermType::List =>
+                self.container(depth, |this| this.list(term, depth)),
+            TermType::Tuple =>
+                self.container(depth, |this| {
+                    let items = get_tuple_ref(term).ok().unwrap();
+                    try!(this.f.write_char('{'));
+                    try!(this.items(items.into_iter(), |this, item| this.term(item, depth + 1)));
+                    this.f.write_char('}')
+                }),
+            TermType::Map =>
+                self.container(depth, |this| this.map(term, depth)),
+            TermType::Pid =>
+                self.f.write_str(if self.elixir() { "#PID<...>" } else { "<...>" }),
+            TermType::Port =>
+                self.f.write_str("#Port<...>"),
+            TermType::Ref =>
+                self.f.write_str(if self.elixir() { "#Reference<...>" } else { "#Ref<...>" }),
+            TermType::Fun =>
+                self.f.write_str(if self.elixir() { "#Function<...>" } else { "#Fun<...>" }),
+            TermType::Exception | TermType::Unknown =>
+                self.f.write_str("#Unknown<...>"),
+        }
+    }
+
+    fn container<F>(&mut self, depth: usize, inner: F) -> fmt::Result
+        where F: FnOnce(&mut Self) -> fmt::Result
+    {
+        if self.too_deep(depth) {
+            self.f.write_str("...")
+        } else {
+            inner(self)
+        }
+    }
+
+    // Writes items separated by commas, stopping with `...` at the item limit.
+    fn items<T, I, F>(&mut self, items: I, mut item: F) -> fmt::Result
+        where I: Iterator<Item = T>, F: FnMut(&mut Self, T) -> fmt::Result
+    {
+        for (index, value) in items.enumerate() {
+            if index > 0 {
+                try!(self.f.write_str(self.separator()));
+            }
+            if self.too_many(index) {
+                return self.f.write_str("...");
+            }
+            try!(item(self, value));
+        }
+        Ok(())
+    }
+
+    fn atom(&mut self, name: &str) -> fmt::Result {
+        if self.elixir() {
+            if name == "true" || name == "false" || name == "nil" {
+                self.f.write_str(name)
+            } else if name.starts_with("Elixir.") && is_elixir_alias(&name[7..]) {
+                self.f.write_str(&name[7..])
+            } else if is_elixir_atom(name) {
+                write!(self.f, ":{}", name)
+            } else {
+                try!(self.f.write_char(':'));
+                self.quoted(name, '"')
+            }
+        } else if is_erlang_unquoted_atom(name) {
+            self.f.write_str(name)
+        } else {
+            self.quoted(name, '\'')
+        }
+    }
+
+    // Writes a string in quotes, escaping the quote character and non-printable characters.
+    fn quoted(&mut self, string: &str, quote: char) -> fmt::Result {
+        try!(self.f.write_char(quote));
+        for c in string.chars() {
+            match c {
+                '\\' => try!(self.f.write_str("\\\\")),
+                '\n' => try!(self.f.write_str("\\n")),
+                '\r' => try!(self.f.write_str("\\r")),
+                '\t' => try!(self.f.write_str("\\t")),
+                c if c == quote => try!(write!(self.f, "\\{}", c)),
+                c if c.is_control() => try!(write!(self.f, "\\x{{{:X}}}", c as u32)),
+                c => try!(self.f.write_char(c)),
+            }
+        }
+        self.f.write_char(quote)
+    }
+
+    fn integer(&mut self, term: NifTerm) -> fmt::Result {
+        if let Ok(value) = term.decode::<i64>() {
+            return write!(self.f, "{}", value);
+        }
+        if let Ok(value) = term.decode::<u64>() {
+            return write!(self.f, "{}", value);
+        }
+        match etf::decode(term.to_binary().as_slice()) {
+            Ok(etf::Term::BigInteger(ref value)) => {
+                if value.negative {
+                    try!(self.f.write_char('-'));
+                }
+                self.f.write_str(&to_decimal(&value.magnitude))
+            },
+            _ => self.f.write_str("#Integer<...>"),
+        }
+    }
+
+    fn float(&mut self, value: f64) -> fmt::Result {
+        // Floats always have a fraction in Elixir and Erlang, like `1.0` and `1.0e20`.
+        let formatted = format!("{:?}", value);
+        match formatted.find('e') {
+            Some(exponent) if !formatted[..exponent].contains('.') =>
+                write!(self.f, "{}.0{}", &formatted[..exponent], &formatted[exponent..]),
+            None if !formatted.contains('.') =>
+                write!(self.f, "{}.0", formatted),
+            _ =>
+                self.f.write_str(&formatted),
+        }
+    }
+
+    fn binary(&mut self, bytes: &[u8]) -> fmt::Result {
+        let limit = self.options.max_items.unwrap_or(bytes.len());
+        if let Ok(string) = str::from_utf8(bytes) {
+            if !string.chars().any(|c| c.is_control() && !"\n\r\t".contains(c)) {
+                let truncated = string.char_indices().nth(limit).map(|(index, _)| &string[..index]);
+                return match (self.elixir(), truncated) {
+                    (true, None) => self.quoted(string, '"'),
+                    (true, Some(truncated)) => {
+                        try!(self.quoted(truncated, '"'));
+                        self.f.write_str(" <> ...")
+                    },
+                    (false, None) => {
+                        try!(self.f.write_str("<<"));
+                        try!(self.quoted(string, '"'));
+                        self.f.write_str(">>")
+                    },
+                    (false, Some(truncated)) => {
+                        try!(self.f.write_str("<<"));
+                        try!(self.quoted(truncated, '"'));
+                        self.f.write_str("...>>")
+                    },
+                };
+            }
+        }
+        try!(self.f.write_str("<<"));
+        try!(self.items(bytes.iter(), |this, byte| write!(this.f, "{}", byte)));
+        self.f.write_str(">>")
+    }
+
+    fn bitstring(&mut self, term: NifTerm) -> fmt::Result {
+        // BIT_BINARY_EXT: the version, the tag, the number of bytes, the number of bits used in
+        // the last byte, and the bytes.
+        let encoded = term.to_binary();
+        let encoded = encoded.as_slice();
+        if encoded.len() < 7 || encoded[1] != etf::tag::BIT_BINARY_EXT {
+            return self.f.write_str("#Bitstring<...>");
+        }
+        let bits = encoded[6];
+        let data = &encoded[7..];
+        let (last, bytes) = match data.split_last() {
+            Some((last, bytes)) => (*last >> (8 - bits), bytes),
+            None => return self.f.write_str("<<>>"),
+        };
+
+        try!(self.f.write_str("<<"));
+        try!(self.items(bytes.iter(), |this, byte| write!(this.f, "{}", byte)));
+        if !self.too_many(bytes.len()) {
+            if !bytes.is_empty() {
+                try!(self.f.write_str(self.separator()));
+            }
+            if self.elixir() {
+                try!(write!(self.f, "{}::size({})", last, bits));
+            } else {
+                try!(write!(self.f, "{}:{}", last, bits));
+            }
+        }
+        self.f.write_str(">>")
+    }
+
+    fn list(&mut self, list: NifTerm, depth: usize) -> fmt::Result {
+        try!(self.f.write_char('['));
+        let mut tail = list;
+        let mut count = 0;
+        while let Ok((head, rest)) = tail.list_get_cell() {
+            if count > 0 {
+                try!(self.f.write_str(self.separator()));
+            }
+            if self.too_many(count) {
+                try!(self.f.write_str("..."));
+                return self.f.write_char(']');
+            }
+            try!(self.term(head, depth + 1));
+            tail = rest;
+            count += 1;
+        }
+        if !tail.is_empty_list() {
+            try!(self.f.write_str(if self.elixir() { " | " } else { "|" }));
+            try!(self.term(tail, depth + 1));
+        }
+        self.f.write_char(']')
+    }
+
+    fn map(&mut self, map: NifTerm, depth: usize) -> fmt::Result {
+        let struct_key = atom::__struct__().to_term(map.get_env());
+        let struct_name = if self.elixir() {
+            map.map_get(struct_key).ok().and_then(|name| name.atom_to_string().ok())
+        } else {
+            None
+        };
+        let all_atom_keys = NifMapIterator::new(map).unwrap().all(|(key, _)| key.is_atom());
+
+        match struct_name {
+            Some(ref name) if name.starts_with("Elixir.") && is_elixir_alias(&name[7..]) =>
+                try!(write!(self.f, "%{}{{", &name[7..])),
+            Some(ref name) => {
+                try!(self.f.write_char('%'));
+                try!(self.atom(name));
+                try!(self.f.write_char('{'));
+            },
+            None =>
+                try!(self.f.write_str(if self.elixir() { "%{" } else { "#{" })),
+        }
+
+        let entries = NifMapIterator::new(map).unwrap()
+            .filter(|&(key, _)| struct_name.is_none() || key != struct_key);
+        try!(self.items(entries, |this, (key, value)| {
+            if this.elixir() && all_atom_keys {
+                let name = key.atom_to_string().ok().unwrap();
+                if is_elixir_atom(&name) {
+                    try!(this.f.write_str(&name));
+                } else {
+                    try!(this.quoted(&name, '"'));
+                }
+                try!(this.f.write_str(": "));
+            } else {
+                try!(this.term(key, depth + 1));
+                try!(this.f.write_str(" => "));
+            }
+            this.term(value, depth + 1)
+        }));
+        self.f.write_char('}')
+    }
+}
+
+// Atoms like `foo`, `foo_bar?`, `foo@bar!` and `Foo`, which don't need quotes after a colon.
+fn is_elixir_atom(name: &str) -> bool {
+    let body = name.trim_end_matches(|c| c == '?' || c == '!');
+    let mut chars = body.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    name.len() - body.len() <= 1 && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '@')
+}
+
+// Module names like `Foo.Bar`, which are shown without the `Elixir.` prefix.
+fn is_elixir_alias(name: &str) -> bool {
+    !name.is_empty() && name.split('.').all(|part| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(c) if c.is_ascii_uppercase() => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            _ => false,
+        }
+    })
+}
+
+const ERLANG_RESERVED_WORDS: &'static [&'static str] = &[
+    "after", "and", "andalso", "band", "begin", "bnot", "bor", "bsl", "bsr", "bxor", "case",
+    "catch", "cond", "div", "end", "fun", "if", "let", "not", "of", "or", "orelse", "receive",
+    "rem", "try", "when", "xor",
+];
+
+fn is_erlang_unquoted_atom(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_lowercase() => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '@') &&
+        !ERLANG_RESERVED_WORDS.contains(&name)
+}
+
+// Converts the magnitude of a big integer, with the least significant byte first, to decimal.
+fn to_decimal(magnitude: &[u8]) -> String {
+    let mut bytes: Vec<u8> = magnitude.iter().rev().cloned().collect();
+    let mut digits = Vec::new();
+    while !bytes.is_empty() {
+        // Divide by 10, keeping the remainder as the next digit.
+        let mut remainder = 0u32;
+        for byte in bytes.iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / 10) as u8;
+            remainder = value % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        while bytes.first() == Some(&0) {
+            bytes.remove(0);
+        }
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
@@ -0,0 +1,77 @@
+/// Builds a term from Elixir-like syntax.
+///
+/// ```ignore
+/// let reply = term!(env, {:ok, %{count: n, items: items, "total" => (a + b)}});
+/// ```
+///
+/// The syntax is:
+///
+/// * `:name` is an atom. The atom is created when the library is loaded, like with `atoms!`.
+/// * `{a, b}` is a tuple, and `[a, b]` is a list.
+/// * `%{key: value}` is a map with atom keys, and `%{key => value}` is a map with any keys.
+/// * Anything else is a Rust expression, which is encoded with `NifEncoder`. Expressions that
+///   contain commas, or that are blocks, lists or tuples in Rust, must be put in parentheses.
+///
+/// An atom value after a key needs a space, like `%{status: :ok}`, because `::` is a path
+/// separator in Rust.
+#[macro_export]
+macro_rules! term {
+    // A single term, from the tokens of it.
+    (internal, $env:ident; : $name:ident) => {
+        rustler_atom!(stringify!($name)).to_term($env)
+    };
+    (internal, $env:ident; { $($items:tt)* }) => {
+        term!(internal_items, tuple, $env; [] [] $($items)*)
+    };
+    (internal, $env:ident; [ $($items:tt)* ]) => {
+        term!(internal_items, list, $env; [] [] $($items)*)
+    };
+    (internal, $env:ident; % { $($items:tt)* }) => {
+        term!(internal_items, map, $env; [] [] $($items)*)
+    };
+    (internal, $env:ident; $value:expr) => {
+        $crate::NifEncoder::encode(&$value, $env)
+    };
+
+    // Splits the items of a tuple, list or map at the commas, putting the tokens of each item in
+    // parentheses.
+    (internal_items, $kind:ident, $env:ident; [ $($done:tt)* ] [ $($item:tt)* ] , $($rest:tt)*) => {
+        term!(internal_items, $kind, $env; [ $($done)* ( $($item)* ) ] [] $($rest)*)
+    };
+    (internal_items, $kind:ident, $env:ident; [ $($done:tt)* ] [ $($item:tt)* ] $next:tt $($rest:tt)*) => {
+        term!(internal_items, $kind, $env; [ $($done)* ] [ $($item)* $next ] $($rest)*)
+    };
+    (internal_items, $kind:ident, $env:ident; [ $($done:tt)* ] [ $($item:tt)+ ]) => {
+        term!(internal_items, $kind, $env; [ $($done)* ( $($item)* ) ] [])
+    };
+    (internal_items, tuple, $env:ident; [ $( ( $($item:tt)* ) )* ] []) => {
+        $crate::types::tuple::make_tuple($env, &[ $( term!(internal, $env; $($item)*) ),* ])
+    };
+    (internal_items, list, $env:ident; [ $( ( $($item:tt)* ) )* ] []) => {{
+        let items: Vec<$crate::NifTerm> = vec![ $( term!(internal, $env; $($item)*) ),* ];
+        $crate::NifEncoder::encode(&items, $env)
+    }};
+    (internal_items, map, $env:ident; [ $( ( $($entry:tt)* ) )* ] []) => {{
+        let entries: &[($crate::NifTerm, $crate::NifTerm)] = &[ $( term!(internal_entry, $env; $($entry)*) ),* ];
+        $crate::types::map::make_map_from_pairs($env, entries)
+    }};
+
+    // A map entry, as a tuple of the key and the value.
+    (internal_entry, $env:ident; $key:ident : $($value:tt)+) => {
+        (term!(internal, $env; :$key), term!(internal, $env; $($value)+))
+    };
+    (internal_entry, $env:ident; $($entry:tt)+) => {
+        term!(internal_entry_key, $env; [] $($entry)+)
+    };
+    (internal_entry_key, $env:ident; [ $($key:tt)+ ] => $($value:tt)+) => {
+        (term!(internal, $env; $($key)+), term!(internal, $env; $($value)+))
+    };
+    (internal_entry_key, $env:ident; [ $($key:tt)* ] $next:tt $($rest:tt)*) => {
+        term!(internal_entry_key, $env; [ $($key)* $next ] $($rest)*)
+    };
+
+    ($env:expr, $($term:tt)+) => {{
+        let env: $crate::NifEnv = $env;
+        term!(internal, env; $($term)+)
+    }};
+}
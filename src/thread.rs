@@ -1,6 +1,6 @@
-use ::{ NifEnv, NifTerm, NifEncoder };
+use ::{ NifEnv, NifTerm };
 use ::env::OwnedEnv;
-use ::types::atom;
+use ::panic::panic_reason;
 use std::thread;
 use std::panic;
 
@@ -35,14 +35,7 @@ pub fn spawn<'a, S, F>(env: NifEnv<'a>, thread_fn: F)
                 Ok(term) => term,
                 Err(err) => {
                     // Try to get an error message from Rust.
-                    let reason =
-                        if let Some(string) = err.downcast_ref::<String>() {
-                            string.encode(env)
-                        } else if let Some(&s) = err.downcast_ref::<&'static str>() {
-                            s.encode(env)
-                        } else {
-                            atom::get_atom_init("nif_panic").to_term(env)
-                        };
+                    let reason = panic_reason(env, &err);
                     env.error_tuple(reason)
                 }
             }
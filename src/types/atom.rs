@@ -1,14 +1,12 @@
-use std::collections::HashMap;
-use std::sync::RwLock;
-use std::sync::Mutex;
-use std::ops::DerefMut;
+use std::ptr;
+use std::sync::atomic::{ AtomicPtr, AtomicUsize, Ordering };
 
-use ::{ NifTerm, NifEnv, NifResult, NifError };
+use ::{ NifTerm, NifEnv, NifResult, NifError, NifEncoder, NifDecoder };
 use ::wrapper::nif_interface::{
     NIF_ENV,
     NIF_TERM,
-    enif_make_atom_len,
     enif_alloc_env,
+    enif_free_env,
 };
 
 // Atoms are a special case of a term. They can be stored and used on all envs regardless of where
@@ -27,7 +25,30 @@ impl NifAtom {
         NifTerm::new(env, self.term)
     }
     unsafe fn make_atom(env: NIF_ENV, name: &str) -> Self {
-        NifAtom::from_nif_term(enif_make_atom_len(env, name.as_ptr() as *const u8, name.len()))
+        NifAtom::from_nif_term(::wrapper::atom::make_atom(env, name))
+    }
+
+    /// Makes an atom with the given name. Names with characters outside of Latin-1 need a VM
+    /// supporting UTF-8 atoms, which is any VM from OTP 20 on.
+    ///
+    /// Atoms are never garbage collected, so this should not be called with names coming from
+    /// outside of the program.
+    ///
+    /// # Errors
+    ///
+    /// Raises a `SystemLimitError` if the name is longer than 255 characters, and an
+    /// `ArgumentError` if the VM doesn't support the characters in it.
+    pub fn from_str<'a>(env: NifEnv<'a>, name: &str) -> NifResult<Self> {
+        unsafe { ::wrapper::atom::try_make_atom(env.as_c_arg(), name) }
+            .map(|term| unsafe { NifAtom::from_nif_term(term) })
+    }
+
+    /// Returns the atom with the given name if it already exists, without creating it. Unlike
+    /// `from_str()`, this is safe to call with names coming from outside of the program, since it
+    /// can't fill up the atom table.
+    pub fn try_existing<'a>(env: NifEnv<'a>, name: &str) -> Option<Self> {
+        unsafe { ::wrapper::atom::make_existing_atom(env.as_c_arg(), name) }
+            .map(|term| unsafe { NifAtom::from_nif_term(term) })
     }
 
     unsafe fn from_nif_term(term: NIF_TERM) -> Self {
@@ -38,16 +59,61 @@ impl NifAtom {
     pub fn from_term(term: NifTerm) -> NifResult<Self> {
         match term.is_atom() {
             true => Ok(unsafe { NifAtom::from_nif_term(term.as_c_arg()) }),
-            false => Err(NifError::BadArg)
+            false => Err(NifError::expected("atom"))
         }
     }
 }
 
+impl NifEncoder for NifAtom {
+    fn encode<'a>(&self, env: NifEnv<'a>) -> NifTerm<'a> {
+        self.to_term(env)
+    }
+}
+
+impl<'a> NifDecoder<'a> for NifAtom {
+    fn decode(term: NifTerm<'a>) -> NifResult<NifAtom> {
+        NifAtom::from_term(term)
+    }
+}
+
+/// An atom decoded from either an atom, or a binary with the name of an existing atom.
+///
+/// Use this instead of `NifAtom` to accept names as binaries from untrusted input, like the
+/// parameters of a request. Decoding a binary never creates an atom, so it fails with a decode
+/// error for names that are not atoms yet.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct ExistingAtom(pub NifAtom);
+
+impl<'a> NifDecoder<'a> for ExistingAtom {
+    fn decode(term: NifTerm<'a>) -> NifResult<ExistingAtom> {
+        if term.is_atom() {
+            return NifAtom::from_term(term).map(ExistingAtom);
+        }
+        let name: &str = try!(term.decode()
+                              .map_err(|_| NifError::expected("atom or binary with the name of an existing atom")));
+        NifAtom::try_existing(term.get_env(), name)
+            .map(ExistingAtom)
+            .ok_or(NifError::expected("name of an existing atom"))
+    }
+}
+
+impl NifEncoder for ExistingAtom {
+    fn encode<'a>(&self, env: NifEnv<'a>) -> NifTerm<'a> {
+        self.0.to_term(env)
+    }
+}
+
+impl From<ExistingAtom> for NifAtom {
+    fn from(atom: ExistingAtom) -> NifAtom {
+        atom.0
+    }
+}
+
 /// ## Atom terms
 impl<'a> NifTerm<'a> {
 
     /// When the term is an atom, this method will return the string
-    /// representation of it.
+    /// representation of it. Atoms with characters outside of Latin-1 are supported.
     ///
     /// If you only need to test for equality, comparing the terms directly
     /// is much faster.
@@ -60,55 +126,197 @@ impl<'a> NifTerm<'a> {
 }
 
 pub fn is_truthy(term: NifTerm) -> bool {
-    !((term.as_c_arg() == get_atom("false").unwrap().as_c_arg()) || (term.as_c_arg() == get_atom("nil").unwrap().as_c_arg()))
+    !((term.as_c_arg() == false_().as_c_arg()) || (term.as_c_arg() == nil().as_c_arg()))
 }
 
-// This should be safe to do because atoms are never removed/changed once they are created.
-unsafe impl Sync for NifAtom {}
-unsafe impl Send for NifAtom {}
+/// Declares atoms that are cached after they are first created, and then looked up without any
+/// locking.
+///
+/// Every atom becomes a function returning the `NifAtom`. By default the atom has the same name
+/// as the function. A different name can be given as a string, for atoms that are not valid Rust
+/// identifiers.
+///
+/// ```ignore
+/// mod atoms {
+///     atoms! {
+///         ok,
+///         error,
+///         not_found = "not-found",
+///     }
+/// }
+///
+/// let ok = atoms::ok().to_term(env);
+/// ```
+#[macro_export]
+macro_rules! atoms {
+    (internal, $name:ident, $str:expr) => {
+        #[allow(dead_code)]
+        pub fn $name() -> $crate::types::atom::NifAtom {
+            rustler_atom!($str)
+        }
+    };
+    (internal, $name:ident, ) => {
+        atoms!(internal, $name, stringify!($name));
+    };
+    { $( $name:ident $( = $str:expr )* ),* $(,)* } => {
+        $( atoms!(internal, $name, $( $str )*); )*
+    };
+}
 
-struct PrivNifEnvWrapper {
-    env: NIF_ENV
+/// Returns the atom with the given name, which must be a constant. This is what `atoms!` and the
+/// derived encoders and decoders use.
+///
+/// The atom is kept in a static `AtomCache`. It is created the first time it is used, and read
+/// from the cache without locking afterwards.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! rustler_atom {
+    ($str:expr) => {{
+        static ATOM: $crate::types::atom::AtomCache = $crate::types::atom::AtomCache::new($str);
+        ATOM.get()
+    }};
 }
-unsafe impl Send for PrivNifEnvWrapper {}
 
-lazy_static! {
-    static ref ATOMS: RwLock<HashMap<&'static str, NifAtom>> = {
-        let mut map = HashMap::new();
+/// Storage for an atom declared with `atoms!`.
+#[doc(hidden)]
+pub struct AtomCache {
+    name: &'static str,
+    // The atom term, or 0 before the atom is created.
+    term: AtomicUsize,
+    // The next cache in the list of filled caches.
+    next: AtomicPtr<AtomCache>,
+}
 
-        let mut env_guard = ATOM_ENV.lock().unwrap();
-        let env = env_guard.deref_mut();
+impl AtomCache {
+    pub const fn new(name: &'static str) -> Self {
+        AtomCache {
+            name: name,
+            term: AtomicUsize::new(0),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
 
-        map.insert("true", unsafe { NifAtom::make_atom(env.env, "true") });
-        map.insert("false", unsafe { NifAtom::make_atom(env.env, "false") });
-        map.insert("nil", unsafe { NifAtom::make_atom(env.env, "nil") });
-        map.insert("__struct__", unsafe { NifAtom::make_atom(env.env, "__struct__") });
+    pub fn get(&'static self) -> NifAtom {
+        let term = self.term.load(Ordering::Relaxed);
+        if term != 0 {
+            return unsafe { NifAtom::from_nif_term(term as NIF_TERM) };
+        }
 
-        RwLock::new(map)
-    };
+        // Creating an atom with the same name again gives the same term, so racing threads all
+        // get the same atom. Only the thread that fills the cache adds it to the list.
+        let atom = unsafe {
+            let env = enif_alloc_env();
+            let atom = NifAtom::make_atom(env, self.name);
+            enif_free_env(env);
+            atom
+        };
+        if self.term.compare_exchange(0, atom.as_c_arg() as usize, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            register_atom_cache(self);
+        }
+        atom
+    }
+}
 
-    static ref ATOM_ENV: Mutex<PrivNifEnvWrapper> = {
-        Mutex::new(PrivNifEnvWrapper { env: unsafe { enif_alloc_env() } })
-    };
+// The filled caches, as a list linked through `AtomCache::next`.
+static ATOM_CACHES: AtomicPtr<AtomCache> = AtomicPtr::new(ptr::null_mut());
+
+fn register_atom_cache(cache: &'static AtomCache) {
+    let cache_ptr = cache as *const AtomCache as *mut AtomCache;
+    let mut head = ATOM_CACHES.load(Ordering::Acquire);
+    loop {
+        cache.next.store(head, Ordering::Relaxed);
+        match ATOM_CACHES.compare_exchange_weak(head, cache_ptr, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return,
+            Err(current) => head = current,
+        }
+    }
+}
+
+fn find_atom_cache(name: &str) -> Option<&'static AtomCache> {
+    let mut cache_ptr = ATOM_CACHES.load(Ordering::Acquire);
+    while let Some(cache) = unsafe { cache_ptr.as_ref() } {
+        if cache.name == name {
+            return Some(cache);
+        }
+        cache_ptr = cache.next.load(Ordering::Relaxed);
+    }
+    None
 }
 
+/// Fills the caches of the atoms used by the encoders and decoders of this crate. Called when the
+/// library is loaded, before any NIF can run.
+#[doc(hidden)]
+pub fn init_atom_caches() {
+    for atom in &[true_, false_, nil, ok, error, badarg, __struct__, __exception__, nif_panic] {
+        atom();
+    }
+}
+
+/// Makes the atom with the given name. For names that are only known at run time, like the module
+/// of an exception, which can't be declared with `atoms!`.
+#[doc(hidden)]
+pub fn make_atom_term<'a>(env: NifEnv<'a>, name: &str) -> NifTerm<'a> {
+    unsafe { NifAtom::make_atom(env.as_c_arg(), name) }.to_term(env)
+}
+
+/// Creates the atom with the given name and caches it.
+#[deprecated(note = "declare the atom with `atoms!` instead")]
 pub fn init_atom(name: &'static str) -> NifAtom {
-    let mut atoms = ATOMS.write().unwrap();
-    let mut env_guard = ATOM_ENV.lock().unwrap();
-    let env = env_guard.deref_mut();
-    let atom = unsafe { NifAtom::make_atom(env.env, name) };
-    atoms.insert(name, atom);
-    atom
+    match find_atom_cache(name) {
+        Some(cache) => cache.get(),
+        // The cache lives as long as the library, like the ones of `atoms!`.
+        None => Box::leak(Box::new(AtomCache::new(name))).get(),
+    }
 }
 
+/// Returns the atom with the given name, if it has been cached by `init_atom()` or `atoms!`.
+#[deprecated(note = "declare the atom with `atoms!` instead")]
 pub fn get_atom(name: &str) -> Option<NifAtom> {
-    ATOMS.read().unwrap().get(name).cloned()
+    find_atom_cache(name).map(|cache| cache.get())
 }
 
+/// Returns the atom with the given name, creating and caching it if needed.
+#[deprecated(note = "declare the atom with `atoms!` instead")]
+#[allow(deprecated)]
 pub fn get_atom_init(name: &'static str) -> NifAtom {
     match get_atom(name) {
-        Some(atom) => return atom,
-        _ => (),
+        Some(atom) => atom,
+        None => init_atom(name),
+    }
+}
+
+// Atoms used by the encoders and decoders of this crate.
+atoms! {
+    true_ = "true",
+    false_ = "false",
+    nil,
+    ok,
+    error,
+    badarg,
+    __struct__,
+    __exception__,
+    nif_panic,
+}
+
+// This should be safe to do because atoms are never removed/changed once they are created.
+unsafe impl Sync for NifAtom {}
+unsafe impl Send for NifAtom {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use super::{ AtomCache, NifAtom, register_atom_cache };
+
+    #[test]
+    #[allow(deprecated)]
+    fn filled_caches_are_found_by_name() {
+        static CACHE: AtomCache = AtomCache::new("rustler_test_atom");
+        CACHE.term.store(42, Ordering::Relaxed);
+        register_atom_cache(&CACHE);
+
+        let atom = unsafe { NifAtom::from_nif_term(42) };
+        assert!(super::get_atom("rustler_test_atom") == Some(atom));
+        assert!(super::get_atom_init("rustler_test_atom") == atom);
+        assert!(super::get_atom("rustler_missing_atom") == None);
     }
-    init_atom(name)
 }
@@ -76,7 +76,7 @@ impl<'a> NifBinary<'a> {
     pub fn from_term(term: NifTerm<'a>) -> Result<Self, NifError> {
         let mut binary = unsafe { ErlNifBinary::new_empty() };
         if unsafe { nif_interface::enif_inspect_binary(term.get_env().as_c_arg(), term.as_c_arg(), binary.as_c_arg()) } == 0 {
-            return Err(NifError::BadArg);
+            return Err(NifError::expected("binary"));
         }
         Ok(NifBinary {
             inner: binary,
@@ -120,3 +120,57 @@ impl<'a> NifTerm<'a> {
     }
 
 }
+
+/// ## External term format
+impl<'a> NifTerm<'a> {
+
+    /// Encodes the term in the external term format.
+    ///
+    /// ### Elixir equivalent
+    /// ```elixir
+    /// :erlang.term_to_binary(self_term)
+    /// ```
+    pub fn to_binary(self) -> NifBinary<'a> {
+        let env = self.get_env();
+        let raw_term = match unsafe { ::wrapper::term::term_to_binary(env.as_c_arg(), self.as_c_arg()) } {
+            Some(raw_term) => raw_term,
+            None => panic!("binary term allocation fail"),
+        };
+        // This should never fail, as we are always passing in a binary term.
+        NifBinary::from_term(NifTerm::new(env, raw_term)).ok().unwrap()
+    }
+
+}
+
+impl<'a> NifEnv<'a> {
+
+    /// Decodes a term from the external term format. Returns the term, and the number of bytes
+    /// that were read from `data`.
+    ///
+    /// Returns `None` if `data` does not start with a valid encoded term.
+    ///
+    /// This can create new atoms, which are never garbage collected. For data from outside of the
+    /// program, use `binary_to_term_safe()`.
+    ///
+    /// ### Elixir equivalent
+    /// ```elixir
+    /// :erlang.binary_to_term(data)
+    /// ```
+    pub fn binary_to_term(self, data: &[u8]) -> Option<(NifTerm<'a>, usize)> {
+        unsafe { ::wrapper::term::binary_to_term(self.as_c_arg(), data, false) }
+            .map(|(term, read)| (NifTerm::new(self, term), read))
+    }
+
+    /// Like `binary_to_term()`, but returns `None` instead of creating new atoms or references to
+    /// functions that don't exist.
+    ///
+    /// ### Elixir equivalent
+    /// ```elixir
+    /// :erlang.binary_to_term(data, [:safe])
+    /// ```
+    pub fn binary_to_term_safe(self, data: &[u8]) -> Option<(NifTerm<'a>, usize)> {
+        unsafe { ::wrapper::term::binary_to_term(self.as_c_arg(), data, true) }
+            .map(|(term, read)| (NifTerm::new(self, term), read))
+    }
+
+}
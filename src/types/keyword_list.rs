@@ -0,0 +1,114 @@
+//! Keyword lists and proplists, which are lists of `{key, value}` tuples with atom keys.
+//!
+//! These are how Elixir options and Erlang property lists are passed around, like the `opts` in
+//! `parse(data, opts \\ [])`.
+
+use ::{ NifTerm, NifEnv, NifError, NifResult, NifDecoder, NifEncoder };
+use ::types::atom::{ self, NifAtom };
+use ::types::list::NifListIterator;
+use ::types::tuple::{ get_tuple_ref, make_tuple };
+
+/// A keyword list, like `[mode: :fast, limit: 10]`.
+///
+/// Keys can appear more than once. Like `Keyword.get/2` and `:proplists.get_value/2`, lookups
+/// return the first value for a key, and `get_values()` returns all of them.
+///
+/// When decoding, a bare atom in the list, as in the Erlang proplist `[verbose, {limit, 10}]`,
+/// is read as `{atom, true}`.
+///
+/// ```ignore
+/// let opts: NifKeywordList = try!(args[1].decode());
+/// let limit: i64 = try!(opts.get_decoded(atoms::limit())).unwrap_or(100);
+/// ```
+pub struct NifKeywordList<'a> {
+    items: Vec<(NifAtom, NifTerm<'a>)>,
+}
+
+impl<'a> NifKeywordList<'a> {
+    /// Makes an empty keyword list.
+    pub fn new() -> Self {
+        NifKeywordList { items: Vec::new() }
+    }
+
+    /// Returns the first value for `key`, if any.
+    pub fn get(&self, key: NifAtom) -> Option<NifTerm<'a>> {
+        self.items.iter()
+            .find(|&&(item_key, _)| item_key == key)
+            .map(|&(_, value)| value)
+    }
+
+    /// Returns the first value for `key` decoded as a `T`, or `None` if the key is not present.
+    pub fn get_decoded<T>(&self, key: NifAtom) -> NifResult<Option<T>> where T: NifDecoder<'a> {
+        match self.get(key) {
+            Some(value) => value.decode().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the first value for the key with the given name. No atom is created for the name,
+    /// so this can be called with names coming from outside of the program.
+    pub fn get_by_name(&self, env: NifEnv<'a>, name: &str) -> Option<NifTerm<'a>> {
+        NifAtom::try_existing(env, name).and_then(|key| self.get(key))
+    }
+
+    /// Returns all values for `key`, in the order they appear in the list.
+    pub fn get_values(&self, key: NifAtom) -> Vec<NifTerm<'a>> {
+        self.items.iter()
+            .filter(|&&(item_key, _)| item_key == key)
+            .map(|&(_, value)| value)
+            .collect()
+    }
+
+    /// Returns whether `key` is in the list.
+    pub fn has_key(&self, key: NifAtom) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Appends an entry to the end of the list. An existing entry for the same key is not
+    /// replaced, and keeps being the one returned by `get()`.
+    pub fn push(&mut self, key: NifAtom, value: NifTerm<'a>) {
+        self.items.push((key, value));
+    }
+
+    /// Returns the number of entries, counting repeated keys.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns an iterator over the entries, in the order they appear in the list.
+    pub fn iter<'b>(&'b self) -> ::std::slice::Iter<'b, (NifAtom, NifTerm<'a>)> {
+        self.items.iter()
+    }
+}
+
+impl<'a> NifDecoder<'a> for NifKeywordList<'a> {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        let iter: NifListIterator = try!(term.decode().map_err(|_| NifError::expected("keyword list")));
+        let mut items = Vec::new();
+        for (index, item) in iter.enumerate() {
+            let entry = match item.decode::<NifAtom>() {
+                Ok(key) => (key, atom::true_().to_term(term.get_env())),
+                Err(_) => match get_tuple_ref(item) {
+                    Ok(tuple) if tuple.len() == 2 && tuple.get(0).unwrap().is_atom() =>
+                        (try!(tuple.get(0).unwrap().decode()), tuple.get(1).unwrap()),
+                    _ => return Err(NifError::expected("{atom, value} tuple").at_index(index)),
+                },
+            };
+            items.push(entry);
+        }
+        Ok(NifKeywordList { items: items })
+    }
+}
+
+impl<'a> NifEncoder for NifKeywordList<'a> {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        let tuples: Vec<NifTerm<'b>> = self.items.iter()
+            .map(|&(key, value)| make_tuple(env, &[key.to_term(env), value.in_env(env)]))
+            .collect();
+        tuples.encode(env)
+    }
+}
@@ -1,9 +1,13 @@
 //! Utilities used for working with erlang linked lists.
 //!
-//! Right now the only supported way to read lists are through the NifListIterator.
+//! Lists are read with `NifListIterator`, or `NifTypedListIterator` to decode the items as they
+//! are read. They are made by encoding slices, `Vec`s and `VecDeque`s, with
+//! `make_list_from_iter`, or with a `ListBuilder`.
 
 use ::{ NifTerm, NifError, NifResult, NifDecoder, NifEncoder, NifEnv };
 use ::wrapper::list;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
 
 /// Enables iteration over the items in the list.
 ///
@@ -38,6 +42,15 @@ use ::wrapper::list;
 /// # result
 /// # }
 /// ```
+///
+/// ## Improper lists
+/// Decoding a `NifListIterator` fails for improper lists, like `[1 | 2]`, so the items of a
+/// decoded iterator are always the whole list. This checks the list with `enif_get_list_length`
+/// before iterating.
+///
+/// To read the items of a list that may be improper, create the iterator with
+/// `NifListIterator::allow_improper()`. It ends at the tail of an improper list, and `tail()`
+/// returns what the iteration ended at. `ImproperList` decodes the items and the tail of any list.
 pub struct NifListIterator<'a> {
     term: NifTerm<'a>,
 }
@@ -55,6 +68,20 @@ impl<'a> NifListIterator<'a> {
         }
     }
 
+    /// Returns an iterator over a list that may be improper. The iteration ends at the tail of an
+    /// improper list instead of failing, and the tail can be read with `tail()`.
+    ///
+    /// Returns `Err` if the term is not a list.
+    pub fn allow_improper(term: NifTerm<'a>) -> NifResult<Self> {
+        NifListIterator::new(term).ok_or(NifError::expected("list"))
+    }
+
+    /// Returns the part of the list that has not been iterated over yet. Once the iterator has
+    /// returned `None`, this is `[]` for a proper list, or the tail of an improper list.
+    pub fn tail(&self) -> NifTerm<'a> {
+        self.term
+    }
+
 }
 
 impl<'a> Iterator for NifListIterator<'a> {
@@ -70,12 +97,9 @@ impl<'a> Iterator for NifListIterator<'a> {
                 Some(NifTerm::new(self.term.get_env(), head))
             }
             None => {
-                if self.term.is_empty_list() {
-                    // We reached the end of the list, finish the iterator.
-                    None
-                } else {
-                    panic!("list iterator found improper list")
-                }
+                // We reached the end of the list, or the tail of a list created with
+                // `allow_improper()`.
+                None
             }
         }
     }
@@ -83,47 +107,224 @@ impl<'a> Iterator for NifListIterator<'a> {
 
 impl<'a> NifDecoder<'a> for NifListIterator<'a> {
     fn decode(term: NifTerm<'a>) -> NifResult<Self> {
-        match NifListIterator::new(term) {
-            Some(iter) => Ok(iter),
-            None => Err(NifError::BadArg)
+        let iter = try!(NifListIterator::allow_improper(term));
+        match term.list_length() {
+            Ok(_) => Ok(iter),
+            Err(_) => Err(NifError::expected("proper list")),
         }
     }
 }
 
-//impl<'a, T> NifEncoder for Iterator<Item = T> where T: NifEncoder {
-//    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
-//        let term_arr: Vec<::wrapper::nif_interface::NIF_TERM> =
-//            self.map(|x| x.encode(env).as_c_arg()).collect();
-//    }
-//}
+/// An iterator over a list that decodes every item as a `T`, without collecting them first.
+///
+/// Decoding fails for improper lists. Items that fail to decode are returned as errors that name
+/// their index in the list.
+///
+/// ```ignore
+/// let items: NifTypedListIterator<i64> = try!(term.decode());
+/// let mut sum = 0;
+/// for item in items {
+///     sum += try!(item);
+/// }
+/// ```
+pub struct NifTypedListIterator<'a, T> {
+    iter: ::std::iter::Enumerate<NifListIterator<'a>>,
+    item: PhantomData<T>,
+}
+
+impl<'a, T> Iterator for NifTypedListIterator<'a, T> where T: NifDecoder<'a> {
+    type Item = NifResult<T>;
+
+    fn next(&mut self) -> Option<NifResult<T>> {
+        self.iter.next().map(|(index, item)| {
+            item.decode::<T>().map_err(|err| err.at_index(index))
+        })
+    }
+}
+
+impl<'a, T> NifDecoder<'a> for NifTypedListIterator<'a, T> where T: NifDecoder<'a> {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        let iter: NifListIterator = try!(term.decode());
+        Ok(NifTypedListIterator {
+            iter: iter.enumerate(),
+            item: PhantomData,
+        })
+    }
+}
+
+// Builds the list from its last item, so no array of the items' terms is needed.
+fn make_list_rev<'a, I>(env: NifEnv<'a>, items: I, tail: NifTerm<'a>) -> NifTerm<'a>
+    where I: DoubleEndedIterator, I::Item: NifEncoder
+{
+    let list = items.rev().fold(tail.as_c_arg(), |list, item| unsafe {
+        list::make_list_cell(env.as_c_arg(), item.encode(env).as_c_arg(), list)
+    });
+    NifTerm::new(env, list)
+}
+
+fn empty_list<'a>(env: NifEnv<'a>) -> NifTerm<'a> {
+    NifTerm::new(env, unsafe { list::make_list(env.as_c_arg(), &[]) })
+}
+
+/// Makes a list of the items of an iterator, without collecting the items first.
+///
+/// The items are taken in order, like with a `ListBuilder`, so any side effects of the iterator
+/// happen in order too.
+///
+/// ```ignore
+/// let squares = make_list_from_iter(env, (1..n).map(|x| x * x));
+/// ```
+pub fn make_list_from_iter<'a, I>(env: NifEnv<'a>, items: I) -> NifTerm<'a>
+    where I: IntoIterator, I::Item: NifEncoder
+{
+    let mut builder = ListBuilder::new(env);
+    builder.extend(items);
+    builder.finish()
+}
+
+impl<T> NifEncoder for [T] where T: NifEncoder {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        make_list_rev(env, self.iter(), empty_list(env))
+    }
+}
+
+impl<T> NifEncoder for Vec<T> where T: NifEncoder {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        make_list_rev(env, self.iter(), empty_list(env))
+    }
+}
+
+impl<T> NifEncoder for VecDeque<T> where T: NifEncoder {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        make_list_rev(env, self.iter(), empty_list(env))
+    }
+}
 
-impl<'a, T> NifEncoder for Vec<T> where T: NifEncoder {
+impl<T> NifEncoder for Box<[T]> where T: NifEncoder {
     fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
-        let term_array: Vec<::wrapper::nif_interface::NIF_TERM> =
-            self.iter().map(|x| x.encode(env).as_c_arg()).collect();
-        NifTerm::new(env, unsafe { list::make_list(env.as_c_arg(), &term_array) })
+        make_list_rev(env, self.iter(), empty_list(env))
     }
 }
 
 impl<'a, T> NifDecoder<'a> for Vec<T> where T: NifDecoder<'a> {
     fn decode(term: NifTerm<'a>) -> NifResult<Self> {
-        let iter: NifListIterator = try!(term.decode());
-        let res: NifResult<Self> = iter
-            .map(|x| x.decode::<T>())
-            .collect();
-        res
+        let iter: NifTypedListIterator<T> = try!(term.decode());
+        iter.collect()
     }
 }
 
+/// Builds a list by appending items one at a time, for when the number of items is not known in
+/// advance.
+///
+/// The items are kept as list cells in reverse order, and the list is reversed once by `finish()`.
+///
+/// ```ignore
+/// let mut builder = ListBuilder::new(env);
+/// while let Some(row) = rows.next() {
+///     builder.push(&row);
+/// }
+/// let list = builder.finish();
+/// ```
+pub struct ListBuilder<'a> {
+    env: NifEnv<'a>,
+    reversed: NifTerm<'a>,
+    len: usize,
+}
+
+impl<'a> ListBuilder<'a> {
+    pub fn new(env: NifEnv<'a>) -> Self {
+        ListBuilder {
+            env: env,
+            reversed: empty_list(env),
+            len: 0,
+        }
+    }
+
+    /// Appends an item to the end of the list.
+    pub fn push<T>(&mut self, item: T) where T: NifEncoder {
+        self.reversed = self.reversed.list_prepend(item.encode(self.env));
+        self.len += 1;
+    }
+
+    /// Returns the number of items pushed so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the list of the pushed items.
+    pub fn finish(self) -> NifTerm<'a> {
+        // This can't fail, as `self.reversed` is always a proper list.
+        self.reversed.list_reverse().ok().unwrap()
+    }
+}
+
+impl<'a, T> Extend<T> for ListBuilder<'a> where T: NifEncoder {
+    fn extend<I>(&mut self, items: I) where I: IntoIterator<Item = T> {
+        for item in items {
+            self.push(item);
+        }
+    }
+}
+
+/// A list that may be improper, like `[1, 2 | 3]`, as its items and its tail. The tail of a
+/// proper list is `[]`.
+///
+/// This can decode and encode iolists, where binaries can be in the tail:
+///
+/// ```ignore
+/// let iolist: ImproperList<NifTerm, NifTerm> = try!(term.decode());
+/// ```
+pub struct ImproperList<T, U> {
+    pub items: Vec<T>,
+    pub tail: U,
+}
+
+impl<'a, T, U> NifDecoder<'a> for ImproperList<T, U> where T: NifDecoder<'a>, U: NifDecoder<'a> {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        let mut iter = try!(NifListIterator::allow_improper(term));
+        let mut items = Vec::new();
+        for (index, item) in iter.by_ref().enumerate() {
+            items.push(try!(item.decode::<T>().map_err(|err| err.at_index(index))));
+        }
+        let index = items.len();
+        let tail = try!(iter.tail().decode::<U>().map_err(|err| err.at_index(index)));
+        Ok(ImproperList {
+            items: items,
+            tail: tail,
+        })
+    }
+}
+
+impl<T, U> NifEncoder for ImproperList<T, U> where T: NifEncoder, U: NifEncoder {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        make_list_rev(env, self.items.iter(), self.tail.encode(env))
+    }
+}
+
+/// Makes a list of `items` that ends with `tail` instead of `[]`. The list is improper unless
+/// `tail` is a list.
+///
+/// ### Elixir equivalent
+/// ```elixir
+/// items ++ tail
+/// ```
+pub fn make_improper_list<'a>(items: &[NifTerm<'a>], tail: NifTerm<'a>) -> NifTerm<'a> {
+    items.iter().rev().fold(tail, |list, &item| list.list_prepend(item))
+}
+
 /// ## List terms
 impl<'a> NifTerm<'a> {
 
     /// Returns an iterator over a list term.
     /// See documentation for NifListIterator for more information.
     ///
-    /// Returns None if the term is not a list.
+    /// Returns Err(NifError::BadArg) if the term is not a proper list.
     pub fn into_list_iterator(self) -> NifResult<NifListIterator<'a>> {
-        NifListIterator::new(self).ok_or(NifError::BadArg)
+        self.decode().map_err(|_| NifError::BadArg)
     }
 
     /// Returns the length of a list term.
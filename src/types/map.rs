@@ -1,19 +1,123 @@
 //! Utilities used to access and create Erlang maps.
 
-use ::{ NifEnv, NifTerm, NifResult, NifError, NifDecoder };
+use std::collections::{ BTreeMap, HashMap };
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use ::{ NifEnv, NifTerm, NifResult, NifError, NifDecoder, NifEncoder };
 use ::wrapper::map;
 
 pub fn map_new<'a>(env: NifEnv<'a>) -> NifTerm<'a> {
     NifTerm::new(env, unsafe { map::map_new(env.as_c_arg()) })
 }
 
+/// Makes a map of key and value terms. If a key appears more than once, the last value is kept.
+///
+/// With the `nif_version_2_14` feature, the map is made in a single call to
+/// `enif_make_map_from_arrays()`, after sorting the pairs to drop repeated keys. Otherwise the
+/// pairs are added one at a time, which copies maps of up to 32 entries on every insert.
+///
+/// ```ignore
+/// let map = make_map_from_pairs(env, &[(atom::ok().to_term(env), value)]);
+/// ```
+pub fn make_map_from_pairs<'a>(env: NifEnv<'a>, pairs: &[(NifTerm<'a>, NifTerm<'a>)]) -> NifTerm<'a> {
+    make_map_from_pairs_impl(env, pairs)
+}
+
+#[cfg(all(feature = "nif_version_2_14", not(windows)))]
+fn make_map_from_pairs_impl<'a>(env: NifEnv<'a>, pairs: &[(NifTerm<'a>, NifTerm<'a>)]) -> NifTerm<'a> {
+    // `enif_make_map_from_arrays()` fails on repeated keys. The sort is stable, so the last pair
+    // of each key is the last one of its run.
+    let mut sorted: Vec<&(NifTerm<'a>, NifTerm<'a>)> = pairs.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut keys = Vec::with_capacity(sorted.len());
+    let mut values = Vec::with_capacity(sorted.len());
+    for (index, &&(key, value)) in sorted.iter().enumerate() {
+        if sorted.get(index + 1).map_or(false, |next| next.0 == key) {
+            continue;
+        }
+        keys.push(key.as_c_arg());
+        values.push(value.as_c_arg());
+    }
+    // This can't fail, as the keys are unique.
+    NifTerm::new(env, unsafe { map::map_from_arrays(env.as_c_arg(), &keys, &values) }.unwrap())
+}
+
+#[cfg(not(all(feature = "nif_version_2_14", not(windows))))]
+fn make_map_from_pairs_impl<'a>(env: NifEnv<'a>, pairs: &[(NifTerm<'a>, NifTerm<'a>)]) -> NifTerm<'a> {
+    pairs.iter().fold(map_new(env), |map, &(key, value)| {
+        // This can't fail, as `map` is always a map.
+        map.map_put(key, value).ok().unwrap()
+    })
+}
+
+/// Makes a map of the key and value pairs of an iterator. If a key appears more than once, the
+/// last value is kept.
+///
+/// This is the equivalent of `collect()` for maps, which can't be implemented with `FromIterator`
+/// since the map needs an environment. Collecting the pairs into a `HashMap` or `BTreeMap` and
+/// encoding it gives the same map.
+///
+/// ```ignore
+/// let counts = make_map_from_iter(env, words.iter().map(|word| (word.text, word.count)));
+/// ```
+pub fn make_map_from_iter<'a, I, K, V>(env: NifEnv<'a>, entries: I) -> NifTerm<'a>
+    where I: IntoIterator<Item = (K, V)>, K: NifEncoder, V: NifEncoder
+{
+    let mut builder = MapBuilder::new(env);
+    builder.extend(entries);
+    builder.finish()
+}
+
+/// Builds a map by adding entries one at a time. Iterators of key and value pairs can be
+/// collected into it with `extend()`:
+///
+/// ```ignore
+/// let mut builder = MapBuilder::new(env);
+/// builder.put("total", total);
+/// builder.extend(rows.iter().map(|row| (row.name.as_str(), row.count)));
+/// let map = builder.finish();
+/// ```
+pub struct MapBuilder<'a> {
+    env: NifEnv<'a>,
+    pairs: Vec<(NifTerm<'a>, NifTerm<'a>)>,
+}
+
+impl<'a> MapBuilder<'a> {
+    pub fn new(env: NifEnv<'a>) -> Self {
+        MapBuilder {
+            env: env,
+            pairs: Vec::new(),
+        }
+    }
+
+    /// Adds an entry to the map, replacing the value of an existing entry with the same key.
+    pub fn put<K, V>(&mut self, key: K, value: V) where K: NifEncoder, V: NifEncoder {
+        let env = self.env;
+        self.pairs.push((key.encode(env), value.encode(env)));
+    }
+
+    /// Returns the map of the added entries.
+    pub fn finish(self) -> NifTerm<'a> {
+        make_map_from_pairs(self.env, &self.pairs)
+    }
+}
+
+impl<'a, K, V> Extend<(K, V)> for MapBuilder<'a> where K: NifEncoder, V: NifEncoder {
+    fn extend<I>(&mut self, entries: I) where I: IntoIterator<Item = (K, V)> {
+        for (key, value) in entries {
+            self.put(key, value);
+        }
+    }
+}
+
 /// ## Map terms
 impl<'a> NifTerm<'a> {
 
     /// Gets the value corresponding to a key in a map term.
     ///
     /// Returns Err(NifError::BadArg) if the term is not a map or if
-    /// key doesn't exist in the map.
+    /// key doesn't exist in the map. Use `map_find()` to tell the two apart.
     ///
     /// ### Elixir equivalent
     /// ```elixir
@@ -27,6 +131,52 @@ impl<'a> NifTerm<'a> {
         }
     }
 
+    /// Gets the value corresponding to a key in a map term, or `None` if the key is not in the
+    /// map. The key can be anything that can be encoded, like a `&str` for a binary key.
+    ///
+    /// Returns `Err` if the term is not a map.
+    ///
+    /// ### Elixir equivalent
+    /// ```elixir
+    /// Map.fetch(self_term, key)
+    /// ```
+    pub fn map_find<K>(self, key: K) -> NifResult<Option<NifTerm<'a>>> where K: NifEncoder {
+        if !self.is_map() {
+            return Err(NifError::expected("map"));
+        }
+        let env = self.get_env();
+        let key = key.encode(env);
+        Ok(unsafe { map::get_map_value(env.as_c_arg(), self.as_c_arg(), key.as_c_arg()) }
+            .map(|value| NifTerm::new(env, value)))
+    }
+
+    /// Gets the value corresponding to a key in a map term decoded as a `T`, or `None` if the key
+    /// is not in the map.
+    ///
+    /// Returns `Err` if the term is not a map, or if the value fails to decode.
+    pub fn map_get_decoded<K, T>(self, key: K) -> NifResult<Option<T>> where K: NifEncoder, T: NifDecoder<'a> {
+        match try!(self.map_find(key)) {
+            Some(value) => value.decode().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the entries of a map term, decoding every key as a `K` and every
+    /// value as a `V`.
+    ///
+    /// Returns `Err` if the term is not a map.
+    ///
+    /// ```ignore
+    /// for entry in try!(term.map_iter::<String, i64>()) {
+    ///     let (name, count) = try!(entry);
+    /// }
+    /// ```
+    pub fn map_iter<K, V>(self) -> NifResult<NifTypedMapIterator<'a, K, V>>
+        where K: NifDecoder<'a>, V: NifDecoder<'a>
+    {
+        self.decode()
+    }
+
     /// Gets the size of a map term.
     ///
     /// Returns Err(NifError::BadArg) if the term is not a map.
@@ -99,17 +249,43 @@ impl<'a> NifTerm<'a> {
 
 }
 
+/// An iterator over the key and value pairs of a map term.
+///
+/// The iterator can be reversed with `rev()`, and can be iterated from both ends at once.
 pub struct NifMapIterator<'a> {
     env: NifEnv<'a>,
-    iter: map::ErlNifMapIterator
+    map: NifTerm<'a>,
+    iter: map::ErlNifMapIterator,
+    // An iterator from the end of the map, created by the first call to `next_back()`.
+    back: Option<map::ErlNifMapIterator>,
+    // The number of entries not returned from either end.
+    remaining: usize,
 }
 
 impl<'a> NifMapIterator<'a> {
     pub fn new(map: NifTerm<'a>) -> Option<NifMapIterator<'a>> {
         let env = map.get_env();
+        let size = match map.map_size() {
+            Ok(size) => size,
+            Err(_) => return None,
+        };
         unsafe {
             map::map_iterator_create(env.as_c_arg(), map.as_c_arg())
-        }.map(|iter| NifMapIterator { env: env, iter: iter })
+        }.map(|iter| NifMapIterator {
+            env: env,
+            map: map,
+            iter: iter,
+            back: None,
+            remaining: size,
+        })
+    }
+
+    /// Turns this into an iterator that decodes every key as a `K` and every value as a `V`.
+    pub fn typed<K, V>(self) -> NifTypedMapIterator<'a, K, V> where K: NifDecoder<'a>, V: NifDecoder<'a> {
+        NifTypedMapIterator {
+            iter: self,
+            types: PhantomData,
+        }
     }
 }
 
@@ -117,6 +293,9 @@ impl<'a> Drop for NifMapIterator<'a> {
     fn drop(&mut self) {
         unsafe {
             map::map_iterator_destroy(self.env.as_c_arg(), &mut self.iter);
+            if let Some(ref mut back) = self.back {
+                map::map_iterator_destroy(self.env.as_c_arg(), back);
+            }
         }
     }
 }
@@ -125,23 +304,125 @@ impl<'a> Iterator for NifMapIterator<'a> {
     type Item = (NifTerm<'a>, NifTerm<'a>);
 
     fn next(&mut self) -> Option<(NifTerm<'a>, NifTerm<'a>)> {
+        if self.remaining == 0 {
+            return None;
+        }
         unsafe {
             map::map_iterator_get_pair(self.env.as_c_arg(), &mut self.iter)
         }.map(|(key, value)| {
             unsafe {
                 map::map_iterator_next(self.env.as_c_arg(), &mut self.iter);
             }
+            self.remaining -= 1;
             (NifTerm::new(self.env, key),
              NifTerm::new(self.env, value))
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for NifMapIterator<'a> {
+    fn next_back(&mut self) -> Option<(NifTerm<'a>, NifTerm<'a>)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.back.is_none() {
+            self.back = unsafe { map::map_iterator_create_tail(self.env.as_c_arg(), self.map.as_c_arg()) };
+        }
+        let env = self.env;
+        let pair = match self.back {
+            Some(ref mut back) => unsafe {
+                let pair = map::map_iterator_get_pair(env.as_c_arg(), back);
+                map::map_iterator_prev(env.as_c_arg(), back);
+                pair
+            },
+            None => None,
+        };
+        pair.map(|(key, value)| {
+            self.remaining -= 1;
+            (NifTerm::new(env, key),
+             NifTerm::new(env, value))
+        })
+    }
 }
 
+impl<'a> ExactSizeIterator for NifMapIterator<'a> {}
+
 impl<'a> NifDecoder<'a> for NifMapIterator<'a> {
     fn decode(term: NifTerm<'a>) -> NifResult<Self> {
         match NifMapIterator::new(term) {
             Some(iter) => Ok(iter),
-            None => Err(NifError::BadArg)
+            None => Err(NifError::expected("map"))
         }
     }
 }
+
+/// An iterator over a map that decodes every key as a `K` and every value as a `V`. Created with
+/// `NifTerm::map_iter()` or `NifMapIterator::typed()`, or by decoding a map term.
+pub struct NifTypedMapIterator<'a, K, V> {
+    iter: NifMapIterator<'a>,
+    types: PhantomData<(K, V)>,
+}
+
+// Errors name the key of the entry, like `args[0]["name"]`.
+fn decode_entry<'a, K, V>(key: NifTerm<'a>, value: NifTerm<'a>) -> NifResult<(K, V)>
+    where K: NifDecoder<'a>, V: NifDecoder<'a>
+{
+    let decoded_key = try!(key.decode().map_err(|err| err.at_key(key)));
+    let decoded_value = try!(value.decode().map_err(|err| err.at_key(key)));
+    Ok((decoded_key, decoded_value))
+}
+
+impl<'a, K, V> Iterator for NifTypedMapIterator<'a, K, V> where K: NifDecoder<'a>, V: NifDecoder<'a> {
+    type Item = NifResult<(K, V)>;
+
+    fn next(&mut self) -> Option<NifResult<(K, V)>> {
+        self.iter.next().map(|(key, value)| decode_entry(key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for NifTypedMapIterator<'a, K, V> where K: NifDecoder<'a>, V: NifDecoder<'a> {
+    fn next_back(&mut self) -> Option<NifResult<(K, V)>> {
+        self.iter.next_back().map(|(key, value)| decode_entry(key, value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for NifTypedMapIterator<'a, K, V> where K: NifDecoder<'a>, V: NifDecoder<'a> {}
+
+impl<'a, K, V> NifDecoder<'a> for NifTypedMapIterator<'a, K, V> where K: NifDecoder<'a>, V: NifDecoder<'a> {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        let iter: NifMapIterator = try!(term.decode());
+        Ok(iter.typed())
+    }
+}
+
+impl<'a, K, V> NifDecoder<'a> for HashMap<K, V> where K: NifDecoder<'a> + Eq + Hash, V: NifDecoder<'a> {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        try!(term.map_iter::<K, V>()).collect()
+    }
+}
+
+impl<K, V> NifEncoder for HashMap<K, V> where K: NifEncoder + Eq + Hash, V: NifEncoder {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        make_map_from_iter(env, self)
+    }
+}
+
+impl<'a, K, V> NifDecoder<'a> for BTreeMap<K, V> where K: NifDecoder<'a> + Ord, V: NifDecoder<'a> {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        try!(term.map_iter::<K, V>()).collect()
+    }
+}
+
+impl<K, V> NifEncoder for BTreeMap<K, V> where K: NifEncoder + Ord, V: NifEncoder {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        make_map_from_iter(env, self)
+    }
+}
@@ -4,8 +4,10 @@ use ::{
     NifResult,
 };
 
+#[macro_use]
 pub mod atom;
 pub mod binary;
+pub mod keyword_list;
 pub mod list;
 pub mod map;
 pub mod primitive;
@@ -21,6 +23,12 @@ pub trait NifDecoder<'a>: Sized+'a {
     fn decode(term: NifTerm<'a>) -> NifResult<Self>;
 }
 
+impl<'a, T> NifEncoder for &'a T where T: NifEncoder + ?Sized {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        (**self).encode(env)
+    }
+}
+
 impl<'a> NifEncoder for NifTerm<'a> {
     fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
         self.in_env(env)
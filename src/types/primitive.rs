@@ -14,7 +14,7 @@ macro_rules! impl_number_transcoder {
                 #![allow(unused_unsafe)]
                 let mut res: $nif_type = Default::default();
                 if unsafe { erlang_nif_sys::$decode_fun(term.get_env().as_c_arg(), term.as_c_arg(), &mut res) } == 0 {
-                    return Err(NifError::BadArg);
+                    return Err(NifError::expected(stringify!($dec_type)));
                 }
                 Ok(res as $dec_type)
             }
@@ -36,20 +36,20 @@ impl_number_transcoder!(i16, i32, enif_make_int, enif_get_int);
 impl_number_transcoder!(u16, u32, enif_make_uint, enif_get_uint);
 impl_number_transcoder!(f32, f64, enif_make_double, enif_get_double);
 
-use super::atom::{ get_atom };
+use super::atom;
 impl NifEncoder for bool {
     fn encode<'a>(&self, env: NifEnv<'a>) -> NifTerm<'a> {
         // This should always succeed, if there these atoms
         // are missing, something is seriously wrong, worthy of a panic.
         if *self {
-            get_atom("true").unwrap().to_term(env)
+            atom::true_().to_term(env)
         } else {
-            get_atom("false").unwrap().to_term(env)
+            atom::false_().to_term(env)
         }
     }
 }
 impl<'a> NifDecoder<'a> for bool {
     fn decode(term: NifTerm<'a>) -> NifResult<bool> {
-        Ok(super::atom::is_truthy(term))
+        Ok(atom::is_truthy(term))
     }
 }
@@ -12,7 +12,7 @@ impl<'a> NifDecoder<'a> for &'a str {
         let binary = try!(NifBinary::from_term(term));
         match ::std::str::from_utf8(binary.as_slice()) {
             Ok(string) => Ok(string),
-            Err(_) => Err(NifError::BadArg),
+            Err(_) => Err(NifError::expected("UTF-8 binary")),
         }
     }
 }
@@ -30,3 +30,9 @@ impl NifEncoder for str {
         bin.release(env).get_term(env)
     }
 }
+
+impl NifEncoder for String {
+    fn encode<'b>(&self, env: NifEnv<'b>) -> NifTerm<'b> {
+        self.as_str().encode(env)
+    }
+}
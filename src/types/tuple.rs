@@ -1,17 +1,119 @@
-use ::{ NifEnv, NifTerm, NifError };
+use ::{ NifEnv, NifTerm, NifError, NifResult, NifDecoder };
 use ::wrapper::tuple;
 use ::wrapper::nif_interface::NIF_TERM;
 
+/// Returns the elements of a tuple term. This copies the elements into a `Vec`, use
+/// `get_tuple_ref()` to read them in place.
 pub fn get_tuple<'a>(term: NifTerm<'a>) -> Result<Vec<NifTerm<'a>>, NifError> {
+    get_tuple_ref(term).map(|tuple| tuple.to_vec())
+}
+
+/// Returns a view of the elements of a tuple term, without copying them.
+pub fn get_tuple_ref<'a>(term: NifTerm<'a>) -> NifResult<NifTupleRef<'a>> {
     let env = term.get_env();
     match unsafe { tuple::get_tuple(env.as_c_arg(), term.as_c_arg()) } {
-        Ok(terms) => Ok(terms.iter().map(|x| NifTerm::new(env, *x)).collect::<Vec<NifTerm>>()),
-        Err(_error) => Err(NifError::BadArg)
+        Ok(terms) => Ok(NifTupleRef { env: env, terms: terms }),
+        Err(_error) => Err(NifError::expected("tuple"))
     }
 }
 
+// Tuples up to this size are made without allocating.
+const STACK_TUPLE_SIZE: usize = 16;
+
 pub fn make_tuple<'a>(env: NifEnv<'a>, terms: &[NifTerm]) -> NifTerm<'a> {
-    let c_terms: Vec<NIF_TERM> = terms.iter().map(|term| term.as_c_arg()).collect();
-    NifTerm::new(env, unsafe { tuple::make_tuple(env.as_c_arg(), &c_terms) })
+    let term = if terms.len() <= STACK_TUPLE_SIZE {
+        let mut c_terms: [NIF_TERM; STACK_TUPLE_SIZE] = [0; STACK_TUPLE_SIZE];
+        for (c_term, term) in c_terms.iter_mut().zip(terms) {
+            *c_term = term.as_c_arg();
+        }
+        unsafe { tuple::make_tuple(env.as_c_arg(), &c_terms[..terms.len()]) }
+    } else {
+        let c_terms: Vec<NIF_TERM> = terms.iter().map(|term| term.as_c_arg()).collect();
+        unsafe { tuple::make_tuple(env.as_c_arg(), &c_terms) }
+    };
+    NifTerm::new(env, term)
+}
+
+/// The elements of a tuple term, read in place from the VM's tuple.
+///
+/// ```ignore
+/// let tuple: NifTupleRef = try!(term.decode());
+/// if tuple.len() == 2 {
+///     let x: i64 = try!(tuple.get(0).unwrap().decode());
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct NifTupleRef<'a> {
+    env: NifEnv<'a>,
+    terms: &'a [NIF_TERM],
+}
+
+impl<'a> NifTupleRef<'a> {
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the element at `index`, or `None` if the tuple is too short.
+    pub fn get(&self, index: usize) -> Option<NifTerm<'a>> {
+        self.terms.get(index).map(|&term| NifTerm::new(self.env, term))
+    }
+
+    /// Returns an iterator over the elements.
+    pub fn iter(&self) -> NifTupleIterator<'a> {
+        NifTupleIterator {
+            env: self.env,
+            terms: self.terms.iter(),
+        }
+    }
+
+    /// Copies the elements into a `Vec`.
+    pub fn to_vec(&self) -> Vec<NifTerm<'a>> {
+        self.iter().collect()
+    }
+}
+
+impl<'a> NifDecoder<'a> for NifTupleRef<'a> {
+    fn decode(term: NifTerm<'a>) -> NifResult<Self> {
+        get_tuple_ref(term)
+    }
+}
+
+impl<'a> IntoIterator for NifTupleRef<'a> {
+    type Item = NifTerm<'a>;
+    type IntoIter = NifTupleIterator<'a>;
+
+    fn into_iter(self) -> NifTupleIterator<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a tuple. Created with `NifTupleRef::iter()`.
+pub struct NifTupleIterator<'a> {
+    env: NifEnv<'a>,
+    terms: ::std::slice::Iter<'a, NIF_TERM>,
+}
+
+impl<'a> Iterator for NifTupleIterator<'a> {
+    type Item = NifTerm<'a>;
+
+    fn next(&mut self) -> Option<NifTerm<'a>> {
+        self.terms.next().map(|&term| NifTerm::new(self.env, term))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.terms.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for NifTupleIterator<'a> {
+    fn next_back(&mut self) -> Option<NifTerm<'a>> {
+        self.terms.next_back().map(|&term| NifTerm::new(self.env, term))
+    }
 }
 
+impl<'a> ExactSizeIterator for NifTupleIterator<'a> {}
@@ -1,10 +1,116 @@
+use std::mem;
+use std::slice;
+
 use super::nif_interface;
-use super::nif_interface::{ NIF_ENV, NIF_TERM, c_uint };
+use super::nif_interface::{ NIF_ENV, NIF_TERM, ErlNifBinary, ERL_NIF_BIN2TERM_SAFE, c_uint };
 use {NifResult, NifError};
+use ::exception::NifException;
+use ::etf::tag::{ VERSION, ATOM_EXT, SMALL_ATOM_EXT, ATOM_UTF8_EXT, SMALL_ATOM_UTF8_EXT };
+
+/// The maximum number of characters in an atom.
+pub const MAX_ATOM_CHARS: usize = 255;
+
+/// Makes an atom from a UTF-8 string.
+///
+/// Names with only Latin-1 characters are passed to `enif_make_atom_len()`. Other names are
+/// decoded from the external term format, which creates UTF-8 atoms on VMs supporting them.
+///
+/// # Errors
+///
+/// A `SystemLimitError` if `name` is longer than 255 characters, and an `ArgumentError` if the VM
+/// does not support the characters in `name`.
+pub unsafe fn try_make_atom(env: NIF_ENV, name: &str) -> NifResult<NIF_TERM> {
+    if name.is_ascii() {
+        if name.len() > MAX_ATOM_CHARS {
+            return Err(atom_too_long(name));
+        }
+        return Ok(nif_interface::enif_make_atom_len(env, name.as_ptr(), name.len()));
+    }
+
+    let num_chars = name.chars().count();
+    if num_chars > MAX_ATOM_CHARS {
+        return Err(atom_too_long(name));
+    }
+
+    if let Some(latin1) = to_latin1(name) {
+        return Ok(nif_interface::enif_make_atom_len(env, latin1.as_ptr(), latin1.len()));
+    }
 
-#[allow(dead_code)]
+    let encoded = encode_etf_atom(name);
+    let mut term: NIF_TERM = 0;
+    if nif_interface::enif_binary_to_term(env, encoded.as_ptr(), encoded.len(), &mut term, 0) == 0 {
+        return Err(NifException::argument_error(
+            format!("the VM does not support the characters in the atom name {:?}", name)).into());
+    }
+    Ok(term)
+}
+
+/// Makes an atom from a name known to be valid, like the ones given in code.
+///
+/// # Panics
+///
+/// If the atom can't be created.
 pub unsafe fn make_atom(env: NIF_ENV, name: &str) -> NIF_TERM {
-    nif_interface::enif_make_atom_len(env, name.as_ptr() as *const u8, name.len())
+    match try_make_atom(env, name) {
+        Ok(term) => term,
+        Err(_) => panic!("can't create atom {:?}", name),
+    }
+}
+
+/// Looks up an existing atom from a UTF-8 string, without creating it.
+///
+/// Returns `None` if there is no atom with the name.
+pub unsafe fn make_existing_atom(env: NIF_ENV, name: &str) -> Option<NIF_TERM> {
+    let mut term: NIF_TERM = 0;
+
+    if name.is_ascii() {
+        if nif_interface::enif_make_existing_atom_len_latin1(env, name.as_ptr(), name.len(), &mut term) == 0 {
+            return None;
+        }
+        return Some(term);
+    }
+
+    if name.chars().count() > MAX_ATOM_CHARS {
+        return None;
+    }
+
+    if let Some(latin1) = to_latin1(name) {
+        if nif_interface::enif_make_existing_atom_len_latin1(env, latin1.as_ptr(), latin1.len(), &mut term) == 0 {
+            return None;
+        }
+        return Some(term);
+    }
+
+    // In safe mode, decoding fails instead of creating an atom that doesn't exist yet.
+    let encoded = encode_etf_atom(name);
+    if nif_interface::enif_binary_to_term(env, encoded.as_ptr(), encoded.len(), &mut term, ERL_NIF_BIN2TERM_SAFE) == 0 {
+        return None;
+    }
+    Some(term)
+}
+
+fn to_latin1(name: &str) -> Option<Vec<u8>> {
+    if name.chars().all(|c| (c as u32) < 256) {
+        Some(name.chars().map(|c| c as u8).collect())
+    } else {
+        None
+    }
+}
+
+fn encode_etf_atom(name: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(name.len() + 4);
+    encoded.push(VERSION);
+    encoded.push(ATOM_UTF8_EXT);
+    encoded.push((name.len() >> 8) as u8);
+    encoded.push(name.len() as u8);
+    encoded.extend_from_slice(name.as_bytes());
+    encoded
+}
+
+fn atom_too_long(name: &str) -> NifError {
+    let prefix: String = name.chars().take(20).collect();
+    NifException::system_limit_error(
+        format!("atom name {:?}... is longer than {} characters", prefix, MAX_ATOM_CHARS)).into()
 }
 
 /// Get the contents of this atom as a string.
@@ -21,7 +127,11 @@ pub unsafe fn get_atom(env: NIF_ENV, term: NIF_TERM) -> NifResult<String> {
     let mut len = 0;
     let success = nif_interface::enif_get_atom_length_latin1(env, term, &mut len);
     if success == 0 {
-        return Err(NifError::BadArg);
+        // Either not an atom, or an atom with characters outside of Latin-1.
+        if nif_interface::enif_is_atom(env, term) == 0 {
+            return Err(NifError::BadArg);
+        }
+        return get_atom_utf8(env, term);
     }
 
     // Get the bytes from the atom into a buffer.
@@ -48,3 +158,39 @@ pub unsafe fn get_atom(env: NIF_ENV, term: NIF_TERM) -> NifResult<String> {
         Ok(out)
     }
 }
+
+// The Latin-1 functions of the NIF API can't read atoms with other characters, so they are read
+// from the external term format instead.
+unsafe fn get_atom_utf8(env: NIF_ENV, term: NIF_TERM) -> NifResult<String> {
+    let mut binary: ErlNifBinary = mem::zeroed();
+    if nif_interface::enif_term_to_binary(env, term, &mut binary) == 0 {
+        return Err(NifError::BadArg);
+    }
+    let result = {
+        let encoded = slice::from_raw_parts(binary.data, binary.size);
+        decode_etf_atom(encoded)
+    };
+    nif_interface::enif_release_binary(&mut binary);
+    result.ok_or(NifError::BadArg)
+}
+
+fn decode_etf_atom(encoded: &[u8]) -> Option<String> {
+    if encoded.len() < 3 || encoded[0] != VERSION {
+        return None;
+    }
+    let (len, data) = match encoded[1] {
+        SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT =>
+            (encoded[2] as usize, &encoded[3..]),
+        ATOM_EXT | ATOM_UTF8_EXT if encoded.len() >= 4 =>
+            (((encoded[2] as usize) << 8) | encoded[3] as usize, &encoded[4..]),
+        _ => return None,
+    };
+    if data.len() < len {
+        return None;
+    }
+    match encoded[1] {
+        ATOM_UTF8_EXT | SMALL_ATOM_UTF8_EXT =>
+            String::from_utf8(data[..len].to_vec()).ok(),
+        _ => Some(data[..len].iter().map(|&b| b as char).collect()),
+    }
+}
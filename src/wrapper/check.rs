@@ -20,3 +20,9 @@ impl_check_fun!(is_pid, nif_interface::enif_is_pid);
 impl_check_fun!(is_port, nif_interface::enif_is_port);
 impl_check_fun!(is_ref, nif_interface::enif_is_ref);
 impl_check_fun!(is_tuple, nif_interface::enif_is_tuple);
+impl_check_fun!(is_number, nif_interface::enif_is_number);
+
+pub unsafe fn is_float(env: NIF_ENV, term: NIF_TERM) -> bool {
+    let mut value = 0.0;
+    nif_interface::enif_get_double(env, term, &mut value) == 1
+}
@@ -37,6 +37,19 @@ pub unsafe fn map_put(env: NIF_ENV, map: NIF_TERM, key: NIF_TERM, value: NIF_TER
     Some(result)
 }
 
+/// Fails if a key appears more than once.
+#[cfg(all(feature = "nif_version_2_14", not(windows)))]
+pub unsafe fn map_from_arrays(env: NIF_ENV, keys: &[NIF_TERM], values: &[NIF_TERM]) -> Option<NIF_TERM> {
+    let mut result: NIF_TERM = mem::uninitialized();
+    let success = nif_interface::enif_make_map_from_arrays(env, keys.as_ptr(), values.as_ptr(),
+                                                           keys.len(), &mut result);
+
+    if success != 1 {
+        return None;
+    }
+    Some(result)
+}
+
 pub unsafe fn map_remove(env: NIF_ENV, map: NIF_TERM, key: NIF_TERM) -> Option<NIF_TERM> {
     let mut result: NIF_TERM = mem::uninitialized();
     let success = nif_interface::enif_make_map_remove(env, map, key, &mut result);
@@ -58,10 +71,17 @@ pub unsafe fn map_update(env: NIF_ENV, map: NIF_TERM, key: NIF_TERM, new_value:
 }
 
 pub unsafe fn map_iterator_create(env: NIF_ENV, map: NIF_TERM) -> Option<ErlNifMapIterator> {
+    map_iterator_create_at(env, map, ErlNifMapIteratorEntry::ERL_NIF_MAP_ITERATOR_HEAD)
+}
+
+pub unsafe fn map_iterator_create_tail(env: NIF_ENV, map: NIF_TERM) -> Option<ErlNifMapIterator> {
+    map_iterator_create_at(env, map, ErlNifMapIteratorEntry::ERL_NIF_MAP_ITERATOR_TAIL)
+}
+
+unsafe fn map_iterator_create_at(env: NIF_ENV, map: NIF_TERM, entry: ErlNifMapIteratorEntry)
+                                 -> Option<ErlNifMapIterator> {
     let mut iter = mem::uninitialized();
-    let success =
-        nif_interface::enif_map_iterator_create(env, map, &mut iter,
-                                                ErlNifMapIteratorEntry::ERL_NIF_MAP_ITERATOR_HEAD);
+    let success = nif_interface::enif_map_iterator_create(env, map, &mut iter, entry);
     if success == 0 {
         None
     } else {
@@ -86,3 +106,7 @@ pub unsafe fn map_iterator_get_pair(env: NIF_ENV, iter: &mut ErlNifMapIterator)
 pub unsafe fn map_iterator_next(env: NIF_ENV, iter: &mut ErlNifMapIterator) {
     nif_interface::enif_map_iterator_next(env, iter);
 }
+
+pub unsafe fn map_iterator_prev(env: NIF_ENV, iter: &mut ErlNifMapIterator) {
+    nif_interface::enif_map_iterator_prev(env, iter);
+}
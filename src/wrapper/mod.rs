@@ -15,5 +15,6 @@ pub mod exception;
 pub mod resource;
 pub mod list;
 pub mod check;
+pub mod term;
 
 pub use self::nif_interface::enif_make_copy as copy_term;
@@ -18,6 +18,8 @@ pub type size_t = usize;
 pub type NIF_ENV = *mut erlang_nif_sys::ErlNifEnv;
 pub type NIF_TERM = size_t;
 pub type NIF_BINARY = *mut erlang_nif_sys::ErlNifBinary;
+pub use self::erlang_nif_sys::ErlNifBinary;
+pub use self::erlang_nif_sys::ERL_NIF_BIN2TERM_SAFE;
 pub type NIF_RESOURCE_TYPE = *const erlang_nif_sys::ErlNifResourceType;
 pub type NIF_PID = *const erlang_nif_sys::ErlNifPid;
 
@@ -56,6 +58,10 @@ pub unsafe fn enif_raise_exception(env: NIF_ENV, reason: NIF_TERM) -> NIF_TERM {
     erlang_nif_sys::enif_raise_exception(env, reason)
 }
 
+pub unsafe fn enif_priv_data(env: NIF_ENV) -> *mut c_void {
+    erlang_nif_sys::enif_priv_data(env)
+}
+
 pub unsafe fn enif_make_copy(dest_env: NIF_ENV, source_term: NIF_TERM) -> NIF_TERM {
     erlang_nif_sys::enif_make_copy(dest_env, source_term)
 }
@@ -94,6 +100,46 @@ pub unsafe fn enif_is_ref(env: NIF_ENV, term: NIF_TERM) -> c_int {
 pub unsafe fn enif_is_tuple(env: NIF_ENV, term: NIF_TERM) -> c_int {
     erlang_nif_sys::enif_is_tuple(env, term)
 }
+pub unsafe fn enif_is_number(env: NIF_ENV, term: NIF_TERM) -> c_int {
+    erlang_nif_sys::enif_is_number(env, term)
+}
+
+// `enif_make_map_from_arrays()` was added in NIF version 2.14 (OTP 21), which erlang_nif_sys
+// doesn't cover yet. On Windows, NIF functions are called through a table that doesn't include it.
+#[cfg(all(feature = "nif_version_2_14", not(windows)))]
+extern "C" {
+    #[link_name = "enif_make_map_from_arrays"]
+    fn enif_make_map_from_arrays_raw(env: NIF_ENV, keys: *const NIF_TERM, values: *const NIF_TERM,
+                                     cnt: size_t, map_out: *mut NIF_TERM) -> c_int;
+}
+#[cfg(all(feature = "nif_version_2_14", not(windows)))]
+pub unsafe fn enif_make_map_from_arrays(env: NIF_ENV, keys: *const NIF_TERM, values: *const NIF_TERM,
+                                        cnt: size_t, map_out: *mut NIF_TERM) -> c_int {
+    enif_make_map_from_arrays_raw(env, keys, values, cnt, map_out)
+}
+
+// `enif_term_type()` was added in NIF version 2.15 (OTP 22), which erlang_nif_sys doesn't cover
+// yet. On Windows, NIF functions are called through a table that doesn't include it.
+#[cfg(all(feature = "nif_version_2_15", not(windows)))]
+extern "C" {
+    #[link_name = "enif_term_type"]
+    fn enif_term_type_raw(env: NIF_ENV, term: NIF_TERM) -> c_int;
+}
+#[cfg(all(feature = "nif_version_2_15", not(windows)))]
+pub unsafe fn enif_term_type(env: NIF_ENV, term: NIF_TERM) -> c_int {
+    enif_term_type_raw(env, term)
+}
+
+// Comparison
+pub unsafe fn enif_compare(lhs: NIF_TERM, rhs: NIF_TERM) -> c_int {
+    erlang_nif_sys::enif_compare(lhs, rhs)
+}
+pub unsafe fn enif_is_identical(lhs: NIF_TERM, rhs: NIF_TERM) -> c_int {
+    erlang_nif_sys::enif_is_identical(lhs, rhs)
+}
+pub unsafe fn enif_hash_phash2(term: NIF_TERM, salt: u64) -> u64 {
+    erlang_nif_sys::enif_hash(erlang_nif_sys::ErlNifHash::ERL_NIF_PHASH2, term, salt)
+}
 
 // Atoms
 pub unsafe fn enif_make_atom_len(env: NIF_ENV, string: *const u8, length: size_t) -> NIF_TERM {
@@ -105,6 +151,17 @@ pub unsafe fn enif_get_atom_latin1(env: NIF_ENV, term: NIF_TERM, buf: *mut u8, s
 pub unsafe fn enif_get_atom_length_latin1(env: NIF_ENV, term: NIF_TERM, len: *mut c_uint) -> c_int {
     erlang_nif_sys::enif_get_atom_length(env, term, len, erlang_nif_sys::ErlNifCharEncoding::ERL_NIF_LATIN1)
 }
+pub unsafe fn enif_make_existing_atom_len_latin1(env: NIF_ENV, string: *const u8, length: size_t, atom: *mut NIF_TERM) -> c_int {
+    erlang_nif_sys::enif_make_existing_atom_len(env, string, length, atom, erlang_nif_sys::ErlNifCharEncoding::ERL_NIF_LATIN1)
+}
+
+// External term format
+pub unsafe fn enif_term_to_binary(env: NIF_ENV, term: NIF_TERM, bin_ref: NIF_BINARY) -> c_int {
+    erlang_nif_sys::enif_term_to_binary(env, term, bin_ref)
+}
+pub unsafe fn enif_binary_to_term(env: NIF_ENV, data: *const u8, size: size_t, term: *mut NIF_TERM, opts: c_int) -> size_t {
+    erlang_nif_sys::enif_binary_to_term(env, data, size, term, opts)
+}
 
 // Binaries
 pub unsafe fn enif_release_binary(bin_ref: NIF_BINARY) {
@@ -174,6 +231,22 @@ pub unsafe fn enif_open_resource_type(env: NIF_ENV, module_str: *const c_uchar,
                                       ) -> NIF_RESOURCE_TYPE {
     erlang_nif_sys::enif_open_resource_type(env, module_str, name, dtor, flags, tried)
 }
+// erlang_nif_sys declares the flags as an enum, so a combination like
+// `ERL_NIF_RT_CREATE | ERL_NIF_RT_TAKEOVER` can't be passed through it. On Windows, NIF functions
+// are called through a table that only takes the enum.
+#[cfg(not(windows))]
+extern "C" {
+    #[link_name = "enif_open_resource_type"]
+    fn enif_open_resource_type_raw(env: NIF_ENV, module_str: *const c_uchar, name: *const c_uchar,
+                                   dtor: Option<NifResourceDtor>, flags: c_int, tried: *mut c_int
+                                   ) -> NIF_RESOURCE_TYPE;
+}
+#[cfg(not(windows))]
+pub unsafe fn enif_open_resource_type_flags(env: NIF_ENV, module_str: *const c_uchar, name: *const c_uchar,
+                                            dtor: Option<NifResourceDtor>, flags: c_int, tried: *mut c_int
+                                            ) -> NIF_RESOURCE_TYPE {
+    enif_open_resource_type_raw(env, module_str, name, dtor, flags, tried)
+}
 pub unsafe fn enif_alloc_resource(typ: NIF_RESOURCE_TYPE, size: usize) -> NIF_RESOURCE_HANDLE {
     erlang_nif_sys::enif_alloc_resource(typ, size)
 }
@@ -200,6 +273,7 @@ pub use self::erlang_nif_sys::{
     enif_map_iterator_create,
     enif_map_iterator_get_pair,
     enif_map_iterator_next,
+    enif_map_iterator_prev,
     enif_map_iterator_destroy,
 };
 
@@ -1,5 +1,7 @@
 use super::nif_interface;
 use super::nif_interface::{ NIF_ENV, NIF_TERM, NifResourceDtor, NifResourceFlags, NIF_RESOURCE_TYPE, NIF_RESOURCE_HANDLE };
+#[cfg(not(windows))]
+use super::nif_interface::{ c_int, NIF_RESOURCE_FLAGS };
 
 pub use super::nif_interface::{
     enif_alloc_resource as alloc_resource,
@@ -14,14 +16,16 @@ use std::ffi::CString;
 use std::mem;
 use std::ptr;
 
+// Only used on Windows, where the flags can't be combined.
+#[allow(dead_code)]
 pub unsafe fn open_resource_type(env: NIF_ENV, name: &str, dtor: Option<NifResourceDtor>, flags: NifResourceFlags
                                  ) -> Option<NIF_RESOURCE_TYPE> {
     // Currently unused as per erlang nif documentation
     let module_p: *const u8 = ptr::null();
-    let name_p: *const u8 = CString::new(name).unwrap().as_bytes_with_nul().as_ptr();
+    let name_c = CString::new(name).unwrap();
     let res = {
         let mut tried: NifResourceFlags = mem::uninitialized();
-        nif_interface::enif_open_resource_type(env, module_p, name_p, dtor, flags, &mut tried)
+        nif_interface::enif_open_resource_type(env, module_p, name_c.as_ptr() as *const u8, dtor, flags, &mut tried)
     };
 
     if res.is_null() {
@@ -31,6 +35,36 @@ pub unsafe fn open_resource_type(env: NIF_ENV, name: &str, dtor: Option<NifResou
     }
 }
 
+/// Opens a resource type with `ERL_NIF_RT_CREATE | ERL_NIF_RT_TAKEOVER`: the instances left
+/// behind by a previous version of the library are taken over if there are any, otherwise the
+/// type is created.
+#[cfg(not(windows))]
+pub unsafe fn open_or_take_over_resource_type(env: NIF_ENV, name: &str, dtor: Option<NifResourceDtor>
+                                              ) -> Option<NIF_RESOURCE_TYPE> {
+    let module_p: *const u8 = ptr::null();
+    let name_c = CString::new(name).unwrap();
+    let flags = NIF_RESOURCE_FLAGS::ERL_NIF_RT_CREATE as c_int | NIF_RESOURCE_FLAGS::ERL_NIF_RT_TAKEOVER as c_int;
+    let mut tried: c_int = 0;
+    let res = nif_interface::enif_open_resource_type_flags(env, module_p, name_c.as_ptr() as *const u8, dtor,
+                                                           flags, &mut tried);
+
+    if res.is_null() {
+        None
+    } else {
+        Some(res)
+    }
+}
+
+/// On Windows the flags can't be combined, so taking over and creating are tried one after the
+/// other.
+#[cfg(windows)]
+pub unsafe fn open_or_take_over_resource_type(env: NIF_ENV, name: &str, dtor: Option<NifResourceDtor>
+                                              ) -> Option<NIF_RESOURCE_TYPE> {
+    use super::nif_interface::NIF_RESOURCE_FLAGS;
+    open_resource_type(env, name, dtor, NIF_RESOURCE_FLAGS::ERL_NIF_RT_TAKEOVER)
+        .or_else(|| open_resource_type(env, name, dtor, NIF_RESOURCE_FLAGS::ERL_NIF_RT_CREATE))
+}
+
 // Functionally incomplete
 pub unsafe fn get_resource(env: NIF_ENV, term: NIF_TERM, typ: NIF_RESOURCE_TYPE) -> Option<NIF_RESOURCE_HANDLE> {
     let mut ret_obj: NIF_RESOURCE_HANDLE = mem::uninitialized();
@@ -0,0 +1,63 @@
+use std::cmp::Ordering;
+use std::mem;
+use std::slice;
+
+use super::nif_interface;
+use super::nif_interface::{ NIF_ENV, NIF_TERM, ErlNifBinary, ERL_NIF_BIN2TERM_SAFE };
+
+/// Compares two terms in Erlang term order, where `1 == 1.0`.
+pub unsafe fn compare(lhs: NIF_TERM, rhs: NIF_TERM) -> Ordering {
+    let res = nif_interface::enif_compare(lhs, rhs);
+    if res < 0 {
+        Ordering::Less
+    } else if res > 0 {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Checks if two terms are exactly equal, like `=:=`.
+pub unsafe fn is_identical(lhs: NIF_TERM, rhs: NIF_TERM) -> bool {
+    nif_interface::enif_is_identical(lhs, rhs) == 1
+}
+
+/// The portable hash of a term, like `erlang:phash2/1`.
+pub unsafe fn phash2(term: NIF_TERM) -> u64 {
+    nif_interface::enif_hash_phash2(term, 0)
+}
+
+/// Encodes a term in the external term format, like `erlang:term_to_binary/1`.
+pub unsafe fn term_to_bytes(env: NIF_ENV, term: NIF_TERM) -> Option<Vec<u8>> {
+    let mut binary: ErlNifBinary = mem::zeroed();
+    if nif_interface::enif_term_to_binary(env, term, &mut binary) == 0 {
+        return None;
+    }
+    let bytes = slice::from_raw_parts(binary.data, binary.size).to_vec();
+    nif_interface::enif_release_binary(&mut binary);
+    Some(bytes)
+}
+
+/// Encodes a term in the external term format, and makes a binary term of it.
+pub unsafe fn term_to_binary(env: NIF_ENV, term: NIF_TERM) -> Option<NIF_TERM> {
+    let mut binary: ErlNifBinary = mem::zeroed();
+    if nif_interface::enif_term_to_binary(env, term, &mut binary) == 0 {
+        return None;
+    }
+    // Ownership of the binary is transferred to the term.
+    Some(nif_interface::enif_make_binary(env, &mut binary as *mut ErlNifBinary))
+}
+
+/// Decodes a term from the external term format. Returns the term and the number of bytes read.
+///
+/// With `safe`, decoding fails for data that would create new atoms or external functions.
+pub unsafe fn binary_to_term(env: NIF_ENV, data: &[u8], safe: bool) -> Option<(NIF_TERM, usize)> {
+    let opts = if safe { ERL_NIF_BIN2TERM_SAFE } else { 0 };
+    let mut term: NIF_TERM = 0;
+    let read = nif_interface::enif_binary_to_term(env, data.as_ptr(), data.len(), &mut term, opts);
+    if read == 0 {
+        None
+    } else {
+        Some((term, read))
+    }
+}
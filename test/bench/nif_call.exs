@@ -0,0 +1,41 @@
+# Measures the time per call of small NIFs, to compare the calling overhead between versions of
+# Rustler. Run it with `mix run bench/nif_call.exs` in the test project, once on each version.
+#
+# The test project builds its NIFs in debug mode. Set `mode: :release` in `mix.exs` for numbers
+# that match a release build.
+defmodule RustlerTest.Bench.NifCall do
+  @calls 1_000_000
+
+  def run do
+    baseline = measure(fn -> :ok end)
+    IO.puts("empty loop: #{format(baseline)}")
+
+    report("make_list/0", baseline, fn -> RustlerTest.make_list() end)
+    report("echo_u8/1", baseline, fn -> RustlerTest.echo_u8(1) end)
+    report("add_u32/2", baseline, fn -> RustlerTest.add_u32(1, 2) end)
+    report("sum_args/3", baseline, fn -> RustlerTest.sum_args(1, 2, 3) end)
+    report("sum_args/10", baseline, fn -> RustlerTest.sum_args(1, 2, 3, 4, 5, 6, 7, 8, 9, 10) end)
+  end
+
+  defp report(name, baseline, fun) do
+    time = measure(fun)
+    IO.puts("#{name}: #{format(time)} (#{format(time - baseline)} over the empty loop)")
+  end
+
+  # Returns the time per call in nanoseconds, after a warmup.
+  defp measure(fun) do
+    loop(fun, div(@calls, 10))
+    {time, :ok} = :timer.tc(fn -> loop(fun, @calls) end)
+    time * 1000 / @calls
+  end
+
+  defp loop(_fun, 0), do: :ok
+  defp loop(fun, n) do
+    fun.()
+    loop(fun, n - 1)
+  end
+
+  defp format(ns), do: "#{Float.round(ns, 1)} ns/call"
+end
+
+RustlerTest.Bench.NifCall.run()
@@ -0,0 +1,92 @@
+//! A second NIF library of the test project, loaded by the tests themselves, so they can check
+//! what happens when loading it fails or panics, and when it is upgraded and unloaded.
+
+#[macro_use]
+extern crate rustler;
+
+use rustler::{ NifEnv, NifTerm, NifError, NifResult, NifEncoder };
+use rustler::env::{ OwnedEnv, ErlNifPid };
+use rustler::priv_data::PrivData;
+use rustler::resource::ResourceCell;
+use std::thread;
+
+rustler_export_nifs!(
+    "Elixir.RustlerTest.Hooks",
+    [("loaded", 0, loaded),
+     ("make_resource", 1, make_resource),
+     ("resource_value", 1, resource_value),
+     ("make_panicking_resource", 0, make_panicking_resource)],
+    Some(on_load),
+    None,
+    Some(on_unload)
+);
+
+mod atoms {
+    atoms! {
+        unloaded,
+    }
+}
+
+struct HookResource {
+    value: i64,
+}
+
+struct PanickingResource;
+
+impl Drop for PanickingResource {
+    fn drop(&mut self) {
+        panic!("PanickingResource dropped");
+    }
+}
+
+// The process that loaded this version of the library.
+struct Loader {
+    pid: ErlNifPid,
+}
+
+fn on_load<'a>(env: NifEnv<'a>, action: String) -> NifResult<()> {
+    match action.as_str() {
+        "ok" => {
+            resource_struct_init!(HookResource, env);
+            resource_struct_init!(PanickingResource, env);
+            env.set_priv_data(Loader { pid: env.pid() });
+            Ok(())
+        },
+        "fail" => Err(NifError::Atom("load_refused")),
+        "panic" => panic!("load function panicked"),
+        _ => Err(NifError::BadArg),
+    }
+}
+
+// Tells the process that loaded this version of the library that it is unloaded.
+fn on_unload<'a>(env: NifEnv<'a>) {
+    let loader: Option<PrivData<Loader>> = env.priv_data();
+    if let Some(loader) = loader {
+        let pid = loader.pid;
+        // Sent from a thread of our own, as the unload function may not run on a thread that
+        // can send with a process-independent environment. It is joined, as its code is gone once
+        // the library is closed.
+        let sender = thread::spawn(move || {
+            OwnedEnv::new().send(pid, |env| atoms::unloaded().encode(env));
+        });
+        let _ = sender.join();
+    }
+}
+
+fn loaded<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    Ok(true.encode(env))
+}
+
+fn make_resource<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let value: i64 = try!(args[0].decode());
+    Ok(ResourceCell::new(HookResource { value: value }).encode(env))
+}
+
+fn resource_value<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let resource: ResourceCell<HookResource> = try!(args[0].decode());
+    Ok(resource.value.encode(env))
+}
+
+fn make_panicking_resource<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    Ok(ResourceCell::new(PanickingResource).encode(env))
+}
@@ -2,6 +2,14 @@ defmodule NifNotLoadedError do
   defexception message: "nif not loaded"
 end
 
+defmodule RustlerTest.TestException do
+  defexception [:message, :code]
+end
+
+defmodule RustlerTest.Point do
+  defstruct [:x, :y]
+end
+
 defmodule RustlerTest do
   @on_load :load_nif
 
@@ -17,24 +25,77 @@ defmodule RustlerTest do
   def add_u32(_, _), do: err
   def add_i32(_, _), do: err
   def tuple_add(_), do: err
+  def tuple_reverse(_), do: err
   def echo_u8(_), do: err
+  def sum_args(_, _, _), do: err
+  def sum_args(_, _, _, _, _, _, _, _, _, _), do: err
 
   def sum_list(_), do: err
   def make_list(), do: err
+  def sum_list_typed(_), do: err
+  def make_lists(), do: err
+  def improper_list_parts(_), do: err
+  def make_improper_list(_, _), do: err
 
   def sum_map_values(_), do: err
   def map_entries_sorted(_), do: err
+  def map_get_count(_), do: err
+  def map_double_values(_), do: err
+  def map_invert(_), do: err
+  def map_from_pairs(_), do: err
+  def map_entries_reversed(_), do: err
+  def map_entries_from_both_ends(_), do: err
+
+  def keyword_get(_, _), do: err
+  def keyword_options(_), do: err
 
   def resource_make(), do: err
   def resource_set_integer_field(_, _), do: err
   def resource_get_integer_field(_), do: err
 
   def make_shorter_subbinary(_), do: err
+  def to_binary(_), do: err
+  def binary_to_term(_, _), do: err
 
   def atom_to_string(_), do: err
+  def atom_lookup(_), do: err
+  def make_atom(_), do: err
+  def reverse_atoms(_), do: err
+  def existing_atom(_), do: err
 
   def threaded_fac(_), do: err
   def threaded_sleep(_), do: err
 
   def sublists(_), do: err
+  def owned_term_echo(_), do: err
+
+  def priv_data_calls(), do: err
+
+  def panic_with_message(), do: err
+  def set_panic_error_tuple(_), do: err
+
+  def parse_integer(_), do: err
+  def error_atom_string(_), do: err
+
+  def raise_argument_error(_), do: err
+  def raise_test_exception(_), do: err
+
+  def order_total(_, _), do: err
+
+  def term_type(_), do: err
+
+  def term_cmp(_, _), do: err
+  def unique_terms(_), do: err
+  def term_format(_, _, _, _), do: err
+  def term_debug(_), do: err
+  def term_macro(_, _), do: err
+
+  def etf_roundtrip(_), do: err
+  def etf_encode(_), do: err
+  def etf_decode(_), do: err
+  def etf_build_on_thread(_), do: err
+
+  def serde_roundtrip(_), do: err
+  def serde_shape_area(_), do: err
+  def serde_make_shapes(), do: err
 end
@@ -0,0 +1,16 @@
+defmodule RustlerTest.Hooks do
+  # Not loaded on its own. The tests load the library with the `load_info` they need.
+  def load(load_info) do
+    require Rustler
+    Rustler.load_nif(:rustler_test, "rustler_test_hooks", load_info)
+  end
+
+  defp err do
+    throw NifNotLoadedError
+  end
+
+  def loaded, do: err
+  def make_resource(_), do: err
+  def resource_value(_), do: err
+  def make_panicking_resource, do: err
+end
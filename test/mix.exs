@@ -30,6 +30,10 @@ defmodule RustlerTest.Mixfile do
         path: "/",
         mode: :debug,
         default_flags: !rust_nightly?,
+    ],
+     rustler_test_hooks: [
+        path: "/hooks",
+        mode: :debug,
     ]]
   end
 end
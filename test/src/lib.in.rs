@@ -1,52 +1,126 @@
 use rustler::{NifEnv, NifTerm};
 
 mod test_primitives;
-use test_primitives::{add_u32, add_i32, tuple_add, echo_u8};
+use test_primitives::{add_u32, add_i32, tuple_add, tuple_reverse, sum_args, echo_u8};
 
 mod test_list;
-use test_list::{sum_list, make_list};
+use test_list::{sum_list, make_list, sum_list_typed, make_lists, improper_list_parts, make_improper_list};
 
 mod test_map;
-use test_map::{sum_map_values, map_entries_sorted};
+use test_map::{sum_map_values, map_entries_sorted, map_get_count, map_double_values, map_invert,
+               map_from_pairs, map_entries_reversed, map_entries_from_both_ends};
+
+mod test_keyword_list;
+use test_keyword_list::{keyword_get, keyword_options};
 
 mod test_resource;
 use test_resource::{resource_make, resource_set_integer_field, resource_get_integer_field};
 
 mod test_binary;
-use test_binary::make_shorter_subbinary;
+use test_binary::{make_shorter_subbinary, to_binary, binary_to_term};
 
 mod test_atom;
-use test_atom::{atom_to_string};
+use test_atom::{atom_to_string, atom_lookup, make_atom, reverse_atoms, existing_atom};
 
 mod test_thread;
 use test_thread::{threaded_fac, threaded_sleep};
 
 mod test_env;
-use test_env::{sublists};
+use test_env::{sublists, owned_term_echo};
+
+mod test_priv_data;
+use test_priv_data::{priv_data_calls};
+
+mod test_panic;
+use test_panic::{panic_with_message, set_panic_error_tuple};
+
+mod test_error;
+use test_error::{parse_integer, error_atom_string};
+
+mod test_exception;
+use test_exception::{raise_argument_error, raise_test_exception};
+
+mod test_decode_error;
+use test_decode_error::{order_total};
+
+mod test_dynamic;
+use test_dynamic::{term_type};
+
+mod test_term;
+use test_term::{term_cmp, unique_terms, term_format, term_debug, term_macro};
+
+mod test_etf;
+use test_etf::{etf_roundtrip, etf_encode, etf_decode, etf_build_on_thread};
+
+mod test_serde;
+use test_serde::{serde_roundtrip, serde_shape_area, serde_make_shapes};
 
 rustler_export_nifs!(
     "Elixir.RustlerTest",
     [("add_u32", 2, add_u32),
      ("add_i32", 2, add_i32),
      ("tuple_add", 1, tuple_add),
+     ("tuple_reverse", 1, tuple_reverse),
      ("echo_u8", 1, echo_u8),
+     ("sum_args", 3, sum_args),
+     ("sum_args", 10, sum_args),
      ("sum_list", 1, sum_list),
      ("make_list", 0, make_list),
+     ("sum_list_typed", 1, sum_list_typed),
+     ("make_lists", 0, make_lists),
+     ("improper_list_parts", 1, improper_list_parts),
+     ("make_improper_list", 2, make_improper_list),
      ("sum_map_values", 1, sum_map_values),
      ("map_entries_sorted", 1, map_entries_sorted),
+     ("map_get_count", 1, map_get_count),
+     ("map_double_values", 1, map_double_values),
+     ("map_invert", 1, map_invert),
+     ("map_from_pairs", 1, map_from_pairs),
+     ("map_entries_reversed", 1, map_entries_reversed),
+     ("map_entries_from_both_ends", 1, map_entries_from_both_ends),
+     ("keyword_get", 2, keyword_get),
+     ("keyword_options", 1, keyword_options),
      ("resource_make", 0, resource_make),
      ("resource_set_integer_field", 2, resource_set_integer_field),
      ("resource_get_integer_field", 1, resource_get_integer_field),
      ("atom_to_string", 1, atom_to_string),
+     ("atom_lookup", 1, atom_lookup),
+     ("make_atom", 1, make_atom),
+     ("reverse_atoms", 1, reverse_atoms),
+     ("existing_atom", 1, existing_atom),
      ("make_shorter_subbinary", 1, make_shorter_subbinary),
+     ("to_binary", 1, to_binary),
+     ("binary_to_term", 2, binary_to_term),
      ("threaded_fac", 1, threaded_fac),
      ("threaded_sleep", 1, threaded_sleep),
-     ("sublists", 1, sublists)],
+     ("sublists", 1, sublists),
+     ("owned_term_echo", 1, owned_term_echo),
+     ("priv_data_calls", 0, priv_data_calls),
+     ("panic_with_message", 0, panic_with_message),
+     ("set_panic_error_tuple", 1, set_panic_error_tuple),
+     ("parse_integer", 1, parse_integer),
+     ("error_atom_string", 1, error_atom_string),
+     ("raise_argument_error", 1, raise_argument_error),
+     ("raise_test_exception", 1, raise_test_exception),
+     ("order_total", 2, order_total),
+     ("term_type", 1, term_type),
+     ("term_cmp", 2, term_cmp),
+     ("unique_terms", 1, unique_terms),
+     ("term_format", 4, term_format),
+     ("term_debug", 1, term_debug),
+     ("term_macro", 2, term_macro),
+     ("etf_roundtrip", 1, etf_roundtrip),
+     ("etf_encode", 1, etf_encode),
+     ("etf_decode", 1, etf_decode),
+     ("etf_build_on_thread", 1, etf_build_on_thread),
+     ("serde_roundtrip", 1, serde_roundtrip),
+     ("serde_shape_area", 1, serde_shape_area),
+     ("serde_make_shapes", 0, serde_make_shapes)],
     Some(on_load)
 );
 
 fn on_load<'a>(env: NifEnv<'a>, _load_info: NifTerm<'a>) -> bool {
     test_resource::on_load(env);
     test_atom::on_load(env);
-    true
+    test_priv_data::on_load(env)
 }
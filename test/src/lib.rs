@@ -5,6 +5,8 @@
 
 #[macro_use]
 extern crate rustler;
+#[macro_use]
+extern crate serde_derive;
 
 // If we are using syntex, include the expanded file. This is what
 // causes bad error messages.
@@ -1,10 +1,44 @@
 use rustler::NifEncoder;
 use rustler::{NifEnv, NifTerm, NifResult};
+use rustler::types::atom::{NifAtom, ExistingAtom};
+
+mod atoms {
+    atoms! {
+        ok,
+        not_found = "not-found",
+    }
+}
 
 pub fn on_load(_env: NifEnv) {
 }
 
-pub fn atom_to_string<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn atom_to_string<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let atom_string = try!(args[0].atom_to_string());
     Ok(atom_string.encode(env))
 }
+
+pub fn atom_lookup<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let found: bool = try!(args[0].decode());
+    if found {
+        Ok(atoms::ok().to_term(env))
+    } else {
+        Ok(atoms::not_found().to_term(env))
+    }
+}
+
+pub fn make_atom<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let name: &str = try!(args[0].decode());
+    let atom = try!(NifAtom::from_str(env, name));
+    Ok(atom.encode(env))
+}
+
+pub fn reverse_atoms<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let mut atoms: Vec<NifAtom> = try!(args[0].decode());
+    atoms.reverse();
+    Ok(atoms.encode(env))
+}
+
+pub fn existing_atom<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let atom: ExistingAtom = try!(args[0].decode());
+    Ok(atom.encode(env))
+}
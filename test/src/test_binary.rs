@@ -1,8 +1,28 @@
-use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
+use rustler::{NifEnv, NifTerm, NifEncoder, NifResult, NifError};
 use rustler::types::binary::NifBinary;
+use rustler::types::tuple::make_tuple;
 
-pub fn make_shorter_subbinary<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn make_shorter_subbinary<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let binary: NifBinary = try!(args[0].decode());
     let length: usize = binary.as_slice().len();
     Ok(try!(binary.make_subbinary(1, length-2)).encode(env))
 }
+
+pub fn to_binary<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    Ok(args[0].to_binary().encode(env))
+}
+
+pub fn binary_to_term<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let binary: NifBinary = try!(args[0].decode());
+    let safe: bool = try!(args[1].decode());
+
+    let decoded = if safe {
+        env.binary_to_term_safe(binary.as_slice())
+    } else {
+        env.binary_to_term(binary.as_slice())
+    };
+    match decoded {
+        Some((term, read)) => Ok(make_tuple(env, &[term, (read as u64).encode(env)])),
+        None => Err(NifError::BadArg),
+    }
+}
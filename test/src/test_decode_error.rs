@@ -0,0 +1,28 @@
+use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
+use rustler::decode_error::NifDecodeErrorMode;
+
+#[derive(NifMap)]
+struct Item {
+    price: u32,
+}
+
+#[derive(NifMap)]
+struct Order {
+    items: Vec<Item>,
+}
+
+// Raises the decode errors of a NIF as `ArgumentError`s, for the tests that check their messages.
+// The rest of the test library keeps raising `badarg`.
+pub fn with_argument_errors<'a, F>(fun: F) -> NifResult<NifTerm<'a>>
+    where F: FnOnce() -> NifResult<NifTerm<'a>> {
+    fun().map_err(|err| err.with_decode_error_mode(NifDecodeErrorMode::ArgumentError))
+}
+
+pub fn order_total<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    with_argument_errors(|| {
+        let discount: u32 = args[0].decode()?;
+        let order: Order = args[1].decode()?;
+        let total: u32 = order.items.iter().map(|item| item.price).sum();
+        Ok((total - discount).encode(env))
+    })
+}
@@ -0,0 +1,7 @@
+use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
+use rustler::types::atom::NifAtom;
+
+pub fn term_type<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let name = format!("{:?}", args[0].get_type()).to_lowercase();
+    Ok(try!(NifAtom::from_str(env, &name)).encode(env))
+}
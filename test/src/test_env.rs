@@ -1,10 +1,11 @@
 use rustler::{NifEnv, NifTerm, NifResult, NifEncoder};
 use rustler::env::{OwnedEnv, SavedTerm};
+use rustler::owned_term::OwnedTerm;
 use rustler::types::list::NifListIterator;
 use rustler::types::atom;
 use std::thread;
 
-pub fn sublists<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn sublists<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let pid = env.pid();
     let mut my_env = OwnedEnv::new();
     let saved_reversed_list = my_env.run(|env| -> NifResult<SavedTerm> {
@@ -38,5 +39,20 @@ pub fn sublists<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTe
         });
     });
 
-    Ok(atom::get_atom_init("ok").to_term(env))
+    Ok(atom::ok().to_term(env))
+}
+
+pub fn owned_term_echo<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let pid = env.pid();
+    let owned: OwnedTerm = args[0].decode()?;
+    let copy = owned.clone();
+
+    // The term can be encoded again after the env it was encoded into is cleared.
+    thread::spawn(move || {
+        let mut my_env = OwnedEnv::new();
+        my_env.send(pid, |env| owned.encode(env));
+        my_env.send(pid, |env| vec![copy.encode(env), (owned == copy).encode(env)].encode(env));
+    });
+
+    Ok(atom::ok().to_term(env))
 }
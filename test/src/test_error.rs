@@ -0,0 +1,12 @@
+use rustler::{NifEnv, NifTerm, NifResult, NifEncoder, NifError};
+
+pub fn parse_integer<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let string: String = args[0].decode()?;
+    let integer: i64 = string.parse()?;
+    Ok(integer.encode(env))
+}
+
+pub fn error_atom_string<'a>(_env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let name: String = args[0].decode()?;
+    Err(NifError::AtomString(name))
+}
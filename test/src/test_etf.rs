@@ -0,0 +1,37 @@
+use std::thread;
+
+use rustler::{ NifEnv, NifTerm, NifResult, NifEncoder };
+use rustler::etf::{ self, Term };
+use rustler::types::binary::{ NifBinary, OwnedNifBinary };
+
+pub fn etf_roundtrip<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let term: Term = try!(args[0].decode());
+    Ok(term.encode(env))
+}
+
+pub fn etf_encode<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let term: Term = try!(args[0].decode());
+    let encoded = etf::encode(&term);
+
+    let mut binary = OwnedNifBinary::alloc(encoded.len()).unwrap();
+    binary.as_mut_slice().copy_from_slice(&encoded);
+    Ok(binary.release(env).encode(env))
+}
+
+pub fn etf_decode<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let binary: NifBinary = try!(args[0].decode());
+    let term = try!(etf::decode(binary.as_slice()));
+    term.load(env)
+}
+
+pub fn etf_build_on_thread<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let n: i64 = try!(args[0].decode());
+
+    // Build the term on a thread without an environment.
+    let term = thread::spawn(move || {
+        let items = (0..n).map(Term::from).collect::<Vec<Term>>();
+        Term::Tuple(vec![Term::atom("ok"), Term::from(items), Term::from("done")])
+    }).join().unwrap();
+
+    Ok(term.encode(env))
+}
@@ -0,0 +1,19 @@
+use rustler::{NifEnv, NifTerm, NifResult};
+use rustler::exception::NifException;
+
+#[derive(NifException)]
+#[module = "Elixir.RustlerTest.TestException"]
+struct TestException {
+    message: String,
+    code: u32,
+}
+
+pub fn raise_argument_error<'a>(_env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let message: String = args[0].decode()?;
+    Err(NifException::argument_error(message).into())
+}
+
+pub fn raise_test_exception<'a>(_env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let code: u32 = args[0].decode()?;
+    Err(TestException { message: "test exception".to_string(), code: code }.into())
+}
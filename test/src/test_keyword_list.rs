@@ -0,0 +1,33 @@
+use rustler;
+use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
+use rustler::types::atom::{self, NifAtom};
+use rustler::types::keyword_list::NifKeywordList;
+use rustler::types::tuple::make_tuple;
+
+use test_decode_error::with_argument_errors;
+
+#[derive(NifKeywordList, Default)]
+struct ParseOptions {
+    limit: i64,
+    verbose: bool,
+    separator: String,
+}
+
+pub fn keyword_get<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    with_argument_errors(|| {
+        let list: NifKeywordList = args[0].decode()?;
+        let key: NifAtom = args[1].decode()?;
+        let first = list.get(key).unwrap_or(atom::nil().to_term(env));
+        Ok(make_tuple(env, &[first, list.get_values(key).encode(env)]))
+    })
+}
+
+pub fn keyword_options<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    with_argument_errors(|| {
+        let mut options: ParseOptions = args[0].decode()?;
+        if options.separator.is_empty() {
+            options.separator = ",".to_string();
+        }
+        Ok(options.encode(env))
+    })
+}
@@ -1,7 +1,9 @@
 use rustler::{NifEnv, NifTerm, NifError, NifEncoder, NifResult};
-use rustler::types::list::NifListIterator;
+use std::collections::VecDeque;
+use rustler::types::list::{NifListIterator, NifTypedListIterator, ImproperList, ListBuilder, make_list_from_iter};
+use rustler::types::tuple::make_tuple;
 
-pub fn sum_list<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn sum_list<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let iter: NifListIterator = try!(args[0].decode());
 
     let res: Result<Vec<i64>, NifError> = iter
@@ -14,7 +16,48 @@ pub fn sum_list<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTe
     }
 }
 
-pub fn make_list<'a>(env: NifEnv<'a>, _args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn make_list<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let list = vec![1, 2, 3];
     Ok(list.encode(env))
 }
+
+pub fn sum_list_typed<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let items: NifTypedListIterator<i64> = try!(args[0].decode());
+    let mut sum = 0;
+    for item in items {
+        sum += try!(item);
+    }
+    Ok(sum.encode(env))
+}
+
+pub fn make_lists<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let slice: &[i64] = &[1, 2, 3];
+    let deque: VecDeque<i64> = slice.iter().cloned().collect();
+    let boxed: Box<[i64]> = slice.to_vec().into_boxed_slice();
+
+    let mut builder = ListBuilder::new(env);
+    builder.push(1);
+    builder.extend(vec![2, 3]);
+
+    Ok(make_tuple(env, &[
+        slice.encode(env),
+        deque.encode(env),
+        boxed.encode(env),
+        make_list_from_iter(env, (1..4).map(|x: i32| x as i64)),
+        // Not a `DoubleEndedIterator`, and the running sum depends on the order of the items.
+        make_list_from_iter(env, (1..4).scan(0, |sum, x: i64| { *sum += x; Some(*sum) })),
+        builder.finish(),
+        ListBuilder::new(env).finish(),
+    ]))
+}
+
+pub fn improper_list_parts<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let list: ImproperList<i64, NifTerm> = try!(args[0].decode());
+    Ok(make_tuple(env, &[list.items.encode(env), list.tail]))
+}
+
+pub fn make_improper_list<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let items: Vec<i64> = try!(args[0].decode());
+    let list = ImproperList { items: items, tail: args[1] };
+    Ok(list.encode(env))
+}
@@ -1,8 +1,12 @@
+use std::collections::HashMap;
 use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
-use rustler::types::map::NifMapIterator;
-use rustler::types::tuple::make_tuple;
+use rustler::types::atom;
+use rustler::types::map::{NifMapIterator, MapBuilder, make_map_from_iter};
+use rustler::types::tuple::{NifTupleRef, make_tuple};
 
-pub fn sum_map_values<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+use test_decode_error::with_argument_errors;
+
+pub fn sum_map_values<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let iter: NifMapIterator = args[0].decode()?;
 
     let res: NifResult<Vec<i64>> = iter
@@ -14,19 +18,72 @@ pub fn sum_map_values<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult
     Ok(total.encode(env))
 }
 
-pub fn map_entries_sorted<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn map_entries_sorted<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let iter: NifMapIterator = args[0].decode()?;
 
-    let mut vec = vec![];
-    for (key, value) in iter {
-        let key_string = key.decode::<String>()?;
-        vec.push((key_string, value));
-    }
+    let mut vec: Vec<(NifTerm, NifTerm)> = iter.collect();
+    vec.sort();
 
-    vec.sort_by_key(|pair| pair.0.clone());
     let erlang_pairs: Vec<NifTerm> =
         vec.into_iter()
-        .map(|(key, value)| make_tuple(env, &[key.encode(env), value]))
+        .map(|(key, value)| make_tuple(env, &[key, value]))
         .collect();
     Ok(erlang_pairs.encode(env))
 }
+
+pub fn map_get_count<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    match try!(args[0].map_get_decoded::<_, i64>("count")) {
+        Some(count) => Ok(count.encode(env)),
+        None => Ok(atom::nil().to_term(env)),
+    }
+}
+
+pub fn map_double_values<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    with_argument_errors(|| {
+        let map: HashMap<String, i64> = try!(args[0].decode());
+        let doubled: HashMap<String, i64> = map.into_iter().map(|(key, value)| (key, value * 2)).collect();
+        Ok(doubled.encode(env))
+    })
+}
+
+pub fn map_invert<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let entries: NifResult<Vec<(NifTerm, NifTerm)>> = try!(args[0].map_iter::<NifTerm, NifTerm>()).collect();
+    let mut builder = MapBuilder::new(env);
+    builder.extend(try!(entries).into_iter().map(|(key, value)| (value, key)));
+    Ok(builder.finish())
+}
+
+pub fn map_from_pairs<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let pairs: Vec<NifTupleRef> = try!(args[0].decode());
+    let pairs = pairs.iter()
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| (pair.get(0).unwrap(), pair.get(1).unwrap()));
+    Ok(make_map_from_iter(env, pairs))
+}
+
+pub fn map_entries_reversed<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let iter: NifMapIterator = try!(args[0].decode());
+    let entries: Vec<NifTerm> = iter.rev()
+        .map(|(key, value)| make_tuple(env, &[key, value]))
+        .collect();
+    Ok(entries.encode(env))
+}
+
+pub fn map_entries_from_both_ends<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let mut iter: NifMapIterator = try!(args[0].decode());
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    loop {
+        match iter.next() {
+            Some((key, value)) => front.push(make_tuple(env, &[key, value])),
+            None => break,
+        }
+        match iter.next_back() {
+            Some((key, value)) => back.push(make_tuple(env, &[key, value])),
+            None => break,
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    Ok(front.encode(env))
+}
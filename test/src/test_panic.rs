@@ -0,0 +1,12 @@
+use rustler::{NifEnv, NifTerm, NifResult, NifEncoder};
+use rustler::panic::{set_panic_mode, NifPanicMode};
+
+pub fn panic_with_message<'a>(_env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    panic!("panic_with_message: expected panic");
+}
+
+pub fn set_panic_error_tuple<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let error_tuple: bool = args[0].decode()?;
+    set_panic_mode(if error_tuple { NifPanicMode::ErrorTuple } else { NifPanicMode::Raise });
+    Ok(error_tuple.encode(env))
+}
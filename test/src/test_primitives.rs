@@ -1,13 +1,16 @@
 use rustler;
 use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
+use rustler::types::tuple::{NifTupleRef, make_tuple};
 
-pub fn add_u32<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+use test_decode_error::with_argument_errors;
+
+pub fn add_u32<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let lhs: u32 = try!(args[0].decode());
     let rhs: u32 = try!(args[1].decode());
 
     Ok((lhs + rhs).encode(env))
 }
-pub fn add_i32<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn add_i32<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let lhs: i32 = try!(args[0].decode());
     let rhs: i32 = try!(args[1].decode());
 
@@ -20,12 +23,28 @@ struct AddTuple {
     rhs: i32,
 }
 
-pub fn tuple_add<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn tuple_add<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let tuple: AddTuple = try!(args[0].decode());
     Ok((tuple.lhs + tuple.rhs).encode(env))
 }
 
-pub fn echo_u8<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn tuple_reverse<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let tuple: NifTupleRef = try!(args[0].decode());
+    let reversed: Vec<NifTerm> = tuple.iter().rev().collect();
+    Ok(make_tuple(env, &reversed))
+}
+
+pub fn sum_args<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    with_argument_errors(|| {
+        let mut sum: i64 = 0;
+        for arg in args {
+            sum += try!(arg.decode::<i64>());
+        }
+        Ok(sum.encode(env))
+    })
+}
+
+pub fn echo_u8<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let num: u8 = try!(args[0].decode());
     Ok(num.encode(env))
 }
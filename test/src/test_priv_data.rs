@@ -0,0 +1,19 @@
+use rustler::{NifEnv, NifTerm, NifError, NifEncoder, NifResult};
+use rustler::priv_data::PrivData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct TestPrivData {
+    calls: AtomicUsize,
+}
+
+pub fn on_load<'a>(env: NifEnv<'a>) -> bool {
+    env.set_priv_data(TestPrivData {
+        calls: AtomicUsize::new(0),
+    })
+}
+
+pub fn priv_data_calls<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let data: PrivData<TestPrivData> = try!(env.priv_data().ok_or(NifError::BadArg));
+    let calls = data.calls.fetch_add(1, Ordering::SeqCst) + 1;
+    Ok((calls as u64).encode(env))
+}
@@ -12,7 +12,7 @@ pub fn on_load<'a>(env: NifEnv<'a>) -> bool {
     true
 }
 
-pub fn resource_make<'a>(env: NifEnv<'a>, _args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn resource_make<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let data = TestResource {
         test_field: RwLock::new(0),
     };
@@ -21,7 +21,7 @@ pub fn resource_make<'a>(env: NifEnv<'a>, _args: &Vec<NifTerm<'a>>) -> NifResult
     Ok(resource.encode(env))
 }
 
-pub fn resource_set_integer_field<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) -> NifResult<NifTerm<'a>> {
+pub fn resource_set_integer_field<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let resource: ResourceCell<TestResource> = try!(args[0].decode());
     let mut test_field = resource.test_field.write().unwrap();
     *test_field = try!(args[1].decode());
@@ -29,7 +29,7 @@ pub fn resource_set_integer_field<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>)
     Ok("ok".encode(env))
 }
 
-pub fn resource_get_integer_field<'a>(env: NifEnv<'a>, args: &Vec<NifTerm<'a>>) ->  NifResult<NifTerm<'a>> {
+pub fn resource_get_integer_field<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) ->  NifResult<NifTerm<'a>> {
     let resource: ResourceCell<TestResource> = try!(args[0].decode());
     let test_field = resource.test_field.read().unwrap();
     Ok(test_field.encode(env))
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
+use rustler::serde::{from_term, to_term, SerdeTerm};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Elixir.RustlerTest.Point")]
+pub struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shape {
+    Empty,
+    Circle(f64),
+    Rect(Point, Point),
+    Polygon { points: Vec<Point> },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Drawing<'a> {
+    name: &'a str,
+    shapes: Vec<Shape>,
+    tags: HashMap<String, u32>,
+    parent: Option<String>,
+    size: (u32, u32),
+}
+
+pub fn serde_roundtrip<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let drawing: SerdeTerm<Drawing> = try!(args[0].decode());
+    Ok(drawing.encode(env))
+}
+
+pub fn serde_shape_area<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let shape: Shape = try!(from_term(args[0]));
+    let area = match shape {
+        Shape::Empty => 0.0,
+        Shape::Circle(radius) => 3.0 * radius * radius,
+        Shape::Rect(from, to) => ((to.x - from.x) * (to.y - from.y)) as f64,
+        Shape::Polygon { points } => points.len() as f64,
+    };
+    Ok(area.encode(env))
+}
+
+pub fn serde_make_shapes<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let shapes = vec![
+        Shape::Empty,
+        Shape::Circle(1.5),
+        Shape::Rect(Point { x: 0, y: 0 }, Point { x: 2, y: 3 }),
+        Shape::Polygon { points: vec![Point { x: 1, y: 2 }] },
+    ];
+    Ok(try!(to_term(env, &shapes)))
+}
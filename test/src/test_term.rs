@@ -0,0 +1,62 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use rustler::{NifEnv, NifTerm, NifEncoder, NifResult};
+use rustler::term_format::TermSyntax;
+
+mod atoms {
+    atoms! {
+        lt,
+        eq,
+        gt,
+    }
+}
+
+pub fn term_cmp<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let ordering = match args[0].cmp(&args[1]) {
+        Ordering::Less => atoms::lt(),
+        Ordering::Equal => atoms::eq(),
+        Ordering::Greater => atoms::gt(),
+    };
+    Ok(ordering.encode(env))
+}
+
+pub fn unique_terms<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let terms: Vec<NifTerm> = try!(args[0].decode());
+    let set: HashSet<NifTerm> = terms.into_iter().collect();
+    let mut unique: Vec<NifTerm> = set.into_iter().collect();
+    unique.sort();
+    Ok(unique.encode(env))
+}
+
+pub fn term_format<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let syntax = match try!(args[1].atom_to_string()).as_str() {
+        "erlang" => TermSyntax::Erlang,
+        _ => TermSyntax::Elixir,
+    };
+    let mut format = args[0].format().syntax(syntax);
+    if let Ok(max_depth) = args[2].decode::<u32>() {
+        format = format.max_depth(max_depth as usize);
+    }
+    if let Ok(max_items) = args[3].decode::<u32>() {
+        format = format.max_items(max_items as usize);
+    }
+    Ok(format.to_string().encode(env))
+}
+
+pub fn term_debug<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    Ok(format!("{:?}", args[0]).encode(env))
+}
+
+pub fn term_macro<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let n: i64 = try!(args[0].decode());
+    let items = args[1];
+    Ok(term!(env, {:ok, %{
+        count: n,
+        items: items,
+        "double" => (n * 2),
+        status: :ok,
+        nested: [:a, {:b, [1, 2.5, "three"]}, %{}, {}, []],
+        (n) => :n,
+    }}))
+}
@@ -5,6 +5,45 @@ defmodule RustlerTest.AtomTest do
     assert RustlerTest.atom_to_string(:test_atom) == "test_atom"
     assert RustlerTest.atom_to_string(true) == "true"
     assert RustlerTest.atom_to_string(:erlang.list_to_atom([197])) == "Å"
+    assert RustlerTest.atom_to_string(:"日本語") == "日本語"
+  end
+
+  test "make atom" do
+    assert RustlerTest.make_atom("test_atom") == :test_atom
+    assert RustlerTest.make_atom("Å") == :"Å"
+    assert RustlerTest.make_atom("日本語") == :"日本語"
+  end
+
+  test "make atom with too long name should raise" do
+    assert_raise SystemLimitError, fn -> RustlerTest.make_atom(String.duplicate("a", 256)) end
+    assert_raise SystemLimitError, fn -> RustlerTest.make_atom(String.duplicate("日", 256)) end
+  end
+
+  test "atom encoding and decoding" do
+    assert RustlerTest.reverse_atoms([:a, :b, :"日本語"]) == [:"日本語", :b, :a]
+    assert_raise ArgumentError, fn -> RustlerTest.reverse_atoms([:a, "b"]) end
+  end
+
+  test "atoms declared with atoms!" do
+    assert RustlerTest.atom_lookup(true) == :ok
+    assert RustlerTest.atom_lookup(false) == :"not-found"
+  end
+
+  test "existing atoms from binaries" do
+    assert RustlerTest.existing_atom(:test_atom) == :test_atom
+    assert RustlerTest.existing_atom("test_atom") == :test_atom
+    assert RustlerTest.existing_atom("Å") == :"Å"
+    assert RustlerTest.existing_atom("日本語") == :"日本語"
+  end
+
+  test "existing atoms are never created" do
+    name = "rustler_test_never_an_atom_#{System.unique_integer([:positive])}"
+    assert_raise ArgumentError, fn -> RustlerTest.existing_atom(name) end
+    assert_raise ArgumentError, fn -> String.to_existing_atom(name) end
+
+    utf8_name = "日本語_#{System.unique_integer([:positive])}"
+    assert_raise ArgumentError, fn -> RustlerTest.existing_atom(utf8_name) end
+    assert_raise ArgumentError, fn -> String.to_existing_atom(utf8_name) end
   end
 
   test "atom to string for non-atom should raise" do
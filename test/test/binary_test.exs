@@ -8,4 +8,28 @@ defmodule RustlerTest.BinaryTest do
   test "invalid subbinary creation" do
     assert_raise ErlangError, fn -> RustlerTest.make_shorter_subbinary("t") end
   end
+
+  test "term to binary" do
+    term = {:ok, [1, 2.5, "three"], %{four: 'four'}}
+    assert RustlerTest.to_binary(term) == :erlang.term_to_binary(term)
+    assert :erlang.binary_to_term(RustlerTest.to_binary(term)) == term
+  end
+
+  test "binary to term" do
+    term = {:ok, [1, 2.5, "three"], %{four: 'four'}}
+    binary = :erlang.term_to_binary(term)
+    assert RustlerTest.binary_to_term(binary, false) == {term, byte_size(binary)}
+    assert RustlerTest.binary_to_term(binary, true) == {term, byte_size(binary)}
+    assert RustlerTest.binary_to_term(binary <> "rest", false) == {term, byte_size(binary)}
+    assert_raise ArgumentError, fn -> RustlerTest.binary_to_term("not a term", false) end
+  end
+
+  test "safe binary to term does not create atoms" do
+    # An atom that does not exist yet, in the external term format.
+    name = "rustler_test_binary_to_term_safe"
+    binary = <<131, 119, byte_size(name)>> <> name
+    assert_raise ArgumentError, fn -> RustlerTest.binary_to_term(binary, true) end
+    assert {atom, _} = RustlerTest.binary_to_term(binary, false)
+    assert Atom.to_string(atom) == name
+  end
 end
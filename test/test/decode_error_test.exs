@@ -0,0 +1,31 @@
+defmodule RustlerTest.DecodeErrorTest do
+  use ExUnit.Case, async: true
+
+  test "decoding succeeds" do
+    assert RustlerTest.order_total(1, %{items: [%{price: 2}, %{price: 3}]}) == 4
+  end
+
+  test "decode errors raise badarg in NIFs that don't change the mode" do
+    assert catch_error(RustlerTest.add_u32(:one, 2)) == :badarg
+  end
+
+  test "decode errors name the expected type and argument" do
+    assert_raise ArgumentError, "expected u32 at args[0]", fn ->
+      RustlerTest.order_total(:one, %{items: []})
+    end
+  end
+
+  test "decode errors include the path into the argument" do
+    assert_raise ArgumentError, "expected u32 at args[1].items[1].price", fn ->
+      RustlerTest.order_total(0, %{items: [%{price: 2}, %{price: "3"}]})
+    end
+
+    assert_raise ArgumentError, "expected key at args[1].items[0].price", fn ->
+      RustlerTest.order_total(0, %{items: [%{}]})
+    end
+
+    assert_raise ArgumentError, "expected list at args[1].items", fn ->
+      RustlerTest.order_total(0, %{items: :none})
+    end
+  end
+end
@@ -0,0 +1,20 @@
+defmodule RustlerTest.DynamicTest do
+  use ExUnit.Case, async: true
+
+  test "term types" do
+    assert RustlerTest.term_type(:atom) == :atom
+    assert RustlerTest.term_type(1) == :integer
+    assert RustlerTest.term_type(12345678901234567890) == :integer
+    assert RustlerTest.term_type(1.5) == :float
+    assert RustlerTest.term_type("binary") == :binary
+    assert RustlerTest.term_type(<<1::size(3)>>) == :bitstring
+    assert RustlerTest.term_type([]) == :emptylist
+    assert RustlerTest.term_type([1]) == :list
+    assert RustlerTest.term_type(%{}) == :map
+    assert RustlerTest.term_type({}) == :tuple
+    assert RustlerTest.term_type(self()) == :pid
+    assert RustlerTest.term_type(make_ref()) == :ref
+    assert RustlerTest.term_type(fn -> :ok end) == :fun
+    assert RustlerTest.term_type(hd(Port.list())) == :port
+  end
+end
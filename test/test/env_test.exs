@@ -22,4 +22,11 @@ defmodule RustlerTest.EnvTest do
         ]
     end
   end
+
+  test "owned terms" do
+    term = {:ok, self(), make_ref(), [1, 2 | 3], %{"a" => [1.5, :b, <<1::3>>]}, 12345678901234567890, 'abc'}
+    :ok = RustlerTest.owned_term_echo(term)
+    assert_receive ^term
+    assert_receive [^term, true]
+  end
 end
@@ -0,0 +1,12 @@
+defmodule RustlerTest.ErrorTest do
+  use ExUnit.Case, async: true
+
+  test "std errors are returned as error tuples" do
+    assert RustlerTest.parse_integer("123") == 123
+    assert RustlerTest.parse_integer("abc") == {:error, "invalid digit found in string"}
+  end
+
+  test "error atoms can be created at run time" do
+    assert RustlerTest.error_atom_string("some_error") == :some_error
+  end
+end
@@ -0,0 +1,71 @@
+defmodule RustlerTest.EtfTest do
+  use ExUnit.Case, async: true
+
+  @terms [
+    :atom,
+    :"héllo",
+    :"日本",
+    0,
+    255,
+    -1,
+    2147483648,
+    -9223372036854775808,
+    9223372036854775808,
+    -123456789012345678901234567890,
+    1.5,
+    -0.0,
+    "binary",
+    <<0, 255>>,
+    [],
+    'charlist',
+    [1, :two, "three", [4.0]],
+    {},
+    {:ok, {1, 2}},
+    List.to_tuple(Enum.to_list(1..300)),
+    %{},
+    %{a: 1, "b" => [%{c: {}}]},
+  ]
+
+  test "terms roundtrip through Rust" do
+    for term <- @terms do
+      assert RustlerTest.etf_roundtrip(term) === term
+    end
+  end
+
+  test "encoding" do
+    for term <- @terms do
+      assert :erlang.binary_to_term(RustlerTest.etf_encode(term)) === term
+    end
+  end
+
+  test "decoding" do
+    for term <- @terms do
+      assert RustlerTest.etf_decode(:erlang.term_to_binary(term)) === term
+    end
+    assert RustlerTest.etf_decode(:erlang.term_to_binary(1.5, minor_version: 0)) === 1.5
+  end
+
+  test "decoding invalid data" do
+    assert RustlerTest.etf_decode(<<131, 97>>) == {:error, "unexpected end of data"}
+    assert RustlerTest.etf_decode(<<131, 97, 1, 2>>) == {:error, "trailing bytes after the term"}
+    assert RustlerTest.etf_decode(<<130, 97, 1>>) == {:error, "unsupported version 130"}
+    assert RustlerTest.etf_decode(:erlang.term_to_binary([1 | 2])) == {:error, "improper list"}
+    assert {:error, "unsupported tag " <> _} = RustlerTest.etf_decode(:erlang.term_to_binary(self()))
+  end
+
+  test "terms without a variant of their own" do
+    ref = make_ref()
+    assert RustlerTest.etf_roundtrip(self()) == self()
+    assert RustlerTest.etf_roundtrip(<<1::3>>) == <<1::3>>
+    assert RustlerTest.etf_roundtrip({ref, [1 | 2]}) == {ref, [1 | 2]}
+  end
+
+  test "deeply nested terms" do
+    deep = Enum.reduce(1..10_000, :ok, fn _, acc -> [acc] end)
+    assert RustlerTest.etf_roundtrip(deep) == deep
+  end
+
+  test "building terms on a thread" do
+    assert RustlerTest.etf_build_on_thread(3) == {:ok, [0, 1, 2], "done"}
+  end
+end
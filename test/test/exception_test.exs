@@ -0,0 +1,19 @@
+defmodule RustlerTest.ExceptionTest do
+  use ExUnit.Case, async: true
+
+  test "raise argument error" do
+    assert_raise ArgumentError, "bad input", fn -> RustlerTest.raise_argument_error("bad input") end
+  end
+
+  test "raise derived exception" do
+    try do
+      RustlerTest.raise_test_exception(42)
+      flunk "expected an exception"
+    rescue
+      e in RustlerTest.TestException ->
+        assert e.message == "test exception"
+        assert e.code == 42
+        assert Exception.message(e) == "test exception"
+    end
+  end
+end
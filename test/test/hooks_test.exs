@@ -0,0 +1,61 @@
+defmodule RustlerTest.HooksTest do
+  # The tests load the same library, so they can't run at the same time.
+  use ExUnit.Case, async: false
+
+  alias RustlerTest.Hooks
+
+  setup do
+    # Every test starts with a fresh copy of the module, without the library loaded.
+    :code.purge(Hooks)
+    :code.delete(Hooks)
+    :code.purge(Hooks)
+    load_new_version()
+    :ok
+  end
+
+  # Loads the module again, which makes the loaded version the old one, like a hot code upgrade.
+  defp load_new_version do
+    {Hooks, binary, file} = :code.get_object_code(Hooks)
+    {:module, Hooks} = :code.load_binary(Hooks, file, binary)
+  end
+
+  test "a failing load function fails the load" do
+    assert {:error, {:load, _}} = Hooks.load("fail")
+    assert catch_throw(Hooks.loaded) == NifNotLoadedError
+  end
+
+  test "a load_info that can't be decoded fails the load" do
+    assert {:error, {:load, _}} = Hooks.load(42)
+    assert catch_throw(Hooks.loaded) == NifNotLoadedError
+  end
+
+  test "a panicking load function fails the load" do
+    assert {:error, {:load, _}} = Hooks.load("panic")
+    assert catch_throw(Hooks.loaded) == NifNotLoadedError
+  end
+
+  test "a panicking resource destructor doesn't take the VM down" do
+    assert Hooks.load("ok") == :ok
+
+    # The resource is destroyed when the process holding the only reference to it exits.
+    {pid, ref} = spawn_monitor(fn -> Hooks.make_panicking_resource() end)
+    assert_receive {:DOWN, ^ref, :process, ^pid, :normal}
+    :erlang.garbage_collect()
+
+    assert Hooks.loaded
+  end
+
+  test "resources survive a hot code upgrade and the old library is unloaded" do
+    assert Hooks.load("ok") == :ok
+    resource = Hooks.make_resource(42)
+
+    load_new_version()
+    assert Hooks.load("ok") == :ok
+    assert Hooks.resource_value(resource) == 42
+    refute_received :unloaded
+
+    :code.purge(Hooks)
+    assert_receive :unloaded
+    assert Hooks.resource_value(resource) == 42
+  end
+end
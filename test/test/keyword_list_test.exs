@@ -0,0 +1,35 @@
+defmodule RustlerTest.KeywordListTest do
+  use ExUnit.Case, async: true
+
+  test "keyword list lookup returns the first value" do
+    assert RustlerTest.keyword_get([a: 1, b: 2, a: 3], :a) == {1, [1, 3]}
+    assert RustlerTest.keyword_get([a: 1], :b) == {nil, []}
+    assert RustlerTest.keyword_get([], :a) == {nil, []}
+  end
+
+  test "proplist flags are read as true" do
+    assert RustlerTest.keyword_get([:verbose, {:limit, 10}], :verbose) == {true, [true]}
+  end
+
+  test "keyword list decoding fails on other lists" do
+    assert_raise ArgumentError, "expected {atom, value} tuple at args[0][1]", fn ->
+      RustlerTest.keyword_get([{:a, 1}, {"b", 2}], :a)
+    end
+    assert_raise ArgumentError, fn -> RustlerTest.keyword_get(%{a: 1}, :a) end
+    assert_raise ArgumentError, fn -> RustlerTest.keyword_get([{:a, 1} | :b], :a) end
+  end
+
+  test "option structs decode from keyword lists" do
+    assert RustlerTest.keyword_options([]) == [limit: 0, verbose: false, separator: ","]
+    assert RustlerTest.keyword_options([verbose: true, limit: 5, other: :ignored]) ==
+      [limit: 5, verbose: true, separator: ","]
+    assert RustlerTest.keyword_options([separator: ";", separator: "|"]) ==
+      [limit: 0, verbose: false, separator: ";"]
+  end
+
+  test "option decoding errors name the option" do
+    assert_raise ArgumentError, "expected i64 at args[0].limit", fn ->
+      RustlerTest.keyword_options([limit: :none])
+    end
+  end
+end
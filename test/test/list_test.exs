@@ -6,7 +6,7 @@ defmodule RustlerTest.ListTest do
   end
 
   test "list iteration fails on improper lists" do
-    assert_raise ErlangError, fn -> RustlerTest.sum_list([1, 4, 2 | :invalid]) end
+    assert_raise ArgumentError, fn -> RustlerTest.sum_list([1, 4, 2 | :invalid]) end
   end
 
   test "list iteration fails on invalid entries" do
@@ -20,4 +20,29 @@ defmodule RustlerTest.ListTest do
   test "simple list construction with sum" do
     assert RustlerTest.sum_list(RustlerTest.make_list) == 6
   end
+
+  test "typed list iteration" do
+    assert RustlerTest.sum_list_typed([1, 2, 1, 4]) == 8
+    assert RustlerTest.sum_list_typed([]) == 0
+    assert_raise ArgumentError, fn -> RustlerTest.sum_list_typed([1, :invalid]) end
+    assert_raise ArgumentError, fn -> RustlerTest.sum_list_typed([1 | 2]) end
+  end
+
+  test "list construction from slices, deques, iterators and builders" do
+    assert RustlerTest.make_lists() ==
+      {[1, 2, 3], [1, 2, 3], [1, 2, 3], [1, 2, 3], [1, 3, 6], [1, 2, 3], []}
+  end
+
+  test "improper list decoding" do
+    assert RustlerTest.improper_list_parts([1, 2 | :tail]) == {[1, 2], :tail}
+    assert RustlerTest.improper_list_parts([1, 2]) == {[1, 2], []}
+    assert RustlerTest.improper_list_parts([]) == {[], []}
+    assert_raise ArgumentError, fn -> RustlerTest.improper_list_parts([1, :a | :tail]) end
+  end
+
+  test "improper list construction" do
+    assert RustlerTest.make_improper_list([1, 2], 3) == [1, 2 | 3]
+    assert RustlerTest.make_improper_list([], :tail) == :tail
+    assert RustlerTest.make_improper_list([1], [2, 3]) == [1, 2, 3]
+  end
 end
@@ -10,4 +10,57 @@ defmodule RustlerTest.MapTest do
     assert [{"a", 1}, {"b", 7}, {"c", 6}, {"d", 0}, {"e", 4}] ==
       RustlerTest.map_entries_sorted(%{"d" => 0, "a" => 1, "b" => 7, "e" => 4, "c" => 6})
   end
+
+  test "map entries are sorted in term order" do
+    assert [{1, :a}, {:b, 2}, {{1}, 3}, {"c", 4}] ==
+      RustlerTest.map_entries_sorted(%{"c" => 4, {1} => 3, :b => 2, 1 => :a})
+  end
+
+  test "map lookup with a missing key" do
+    assert RustlerTest.map_get_count(%{"count" => 3}) == 3
+    assert RustlerTest.map_get_count(%{"other" => 3}) == nil
+    assert RustlerTest.map_get_count(%{count: 3}) == nil
+    assert_raise ArgumentError, fn -> RustlerTest.map_get_count(%{"count" => :three}) end
+    assert_raise ArgumentError, fn -> RustlerTest.map_get_count([count: 3]) end
+  end
+
+  test "typed map decoding and encoding" do
+    assert RustlerTest.map_double_values(%{"a" => 1, "b" => -2}) == %{"a" => 2, "b" => -4}
+    assert RustlerTest.map_double_values(%{}) == %{}
+    assert_raise ArgumentError, fn -> RustlerTest.map_double_values(%{a: 1}) end
+    assert_raise ArgumentError, ~s(expected i64 at args[0]["b"]), fn ->
+      RustlerTest.map_double_values(%{"a" => 1, "b" => :x})
+    end
+  end
+
+  test "map construction from an iterator" do
+    assert RustlerTest.map_invert(%{a: 1, b: 2}) == %{1 => :a, 2 => :b}
+  end
+
+  test "map construction keeps the last value of repeated keys" do
+    assert RustlerTest.map_from_pairs([]) == %{}
+    assert RustlerTest.map_from_pairs([{:a, 1}, {:b, 2}, {:a, 3}, {1, :int}, {1.0, :float}, {:c}]) ==
+      %{:a => 3, :b => 2, 1 => :int, 1.0 => :float}
+
+    pairs = Enum.map(1..100, &{rem(&1, 40), &1})
+    assert RustlerTest.map_from_pairs(pairs) == Map.new(pairs)
+  end
+
+  test "reverse map iteration" do
+    small = %{a: 1, b: 2, c: 3}
+    assert RustlerTest.map_entries_reversed(small) == Enum.reverse(Map.to_list(small))
+    assert RustlerTest.map_entries_reversed(%{}) == []
+
+    large = Map.new(1..100, fn i -> {i, i * i} end)
+    assert Enum.sort(RustlerTest.map_entries_reversed(large)) == Enum.sort(Map.to_list(large))
+  end
+
+  test "map iteration from both ends" do
+    for size <- [0, 1, 2, 5, 100] do
+      map = Map.new(Enum.take(1..100, size), fn i -> {i, i} end)
+      entries = RustlerTest.map_entries_from_both_ends(map)
+      assert length(entries) == map_size(map)
+      assert Enum.sort(entries) == Enum.sort(Map.to_list(map))
+    end
+  end
 end
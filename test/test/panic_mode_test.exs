@@ -0,0 +1,15 @@
+defmodule RustlerTest.PanicModeTest do
+  # The panic mode applies to the whole library, so these tests must not run at the same time as
+  # the tests of other modules.
+  use ExUnit.Case, async: false
+
+  setup do
+    on_exit(fn -> RustlerTest.set_panic_error_tuple(false) end)
+  end
+
+  test "panicking nif returns an error tuple in error tuple mode" do
+    RustlerTest.set_panic_error_tuple(true)
+    assert RustlerTest.panic_with_message() ==
+      {:error, {:nif_panic, "panic_with_message: expected panic"}}
+  end
+end
@@ -0,0 +1,12 @@
+defmodule RustlerTest.PanicTest do
+  use ExUnit.Case, async: true
+
+  test "panicking nif raises nif_panic with details" do
+    {:nif_panic, info} = catch_error(RustlerTest.panic_with_message)
+    assert info.message == "panic_with_message: expected panic"
+    assert info.function == :panic_with_message
+    assert info.arity == 0
+    assert info.file =~ "test_panic.rs"
+    assert is_integer(info.line)
+  end
+end
@@ -13,4 +13,24 @@ defmodule RustlerTest.PrimitivesTest do
     assert_raise ArgumentError, fn -> RustlerTest.add_u32("1", 1) end
     assert_raise ArgumentError, fn -> RustlerTest.add_i32(2147483648, 1) end
   end
+
+  test "arguments are passed in order" do
+    assert RustlerTest.sum_args(1, 20, 300) == 321
+    assert RustlerTest.sum_args(1, 2, 3, 4, 5, 6, 7, 8, 9, 10) == 55
+    assert_raise ArgumentError, "expected i64 at args[9]", fn ->
+      RustlerTest.sum_args(1, 2, 3, 4, 5, 6, 7, 8, 9, :ten)
+    end
+  end
+
+  test "tuple decoding and encoding" do
+    assert 3 == RustlerTest.tuple_add({1, 2})
+    assert_raise ArgumentError, fn -> RustlerTest.tuple_add({1, 2, 3}) end
+    assert_raise ArgumentError, fn -> RustlerTest.tuple_add([1, 2]) end
+
+    assert RustlerTest.tuple_reverse({}) == {}
+    assert RustlerTest.tuple_reverse({1, :a, "b"}) == {"b", :a, 1}
+
+    large = List.to_tuple(Enum.to_list(1..40))
+    assert RustlerTest.tuple_reverse(large) == List.to_tuple(Enum.to_list(40..1))
+  end
 end
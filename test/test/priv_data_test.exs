@@ -0,0 +1,9 @@
+defmodule RustlerTest.PrivDataTest do
+  use ExUnit.Case, async: true
+
+  test "private data is kept between calls" do
+    first = RustlerTest.priv_data_calls
+    assert first >= 1
+    assert RustlerTest.priv_data_calls > first
+  end
+end
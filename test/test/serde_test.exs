@@ -0,0 +1,49 @@
+defmodule RustlerTest.SerdeTest do
+  use ExUnit.Case, async: true
+
+  alias RustlerTest.Point
+
+  test "serialize" do
+    assert RustlerTest.serde_make_shapes() == [
+      :empty,
+      {:circle, 1.5},
+      {:rect, %Point{x: 0, y: 0}, %Point{x: 2, y: 3}},
+      {:polygon, %{points: [%Point{x: 1, y: 2}]}},
+    ]
+  end
+
+  test "deserialize" do
+    assert RustlerTest.serde_shape_area(:empty) == 0.0
+    assert RustlerTest.serde_shape_area({:circle, 2.0}) == 12.0
+    assert RustlerTest.serde_shape_area({:rect, %Point{x: 1, y: 1}, %Point{x: 3, y: 4}}) == 6.0
+    assert RustlerTest.serde_shape_area({:polygon, %{points: [%Point{x: 1, y: 2}, %Point{x: 3, y: 4}]}}) == 2.0
+  end
+
+  test "roundtrip" do
+    drawing = %{
+      name: "drawing",
+      shapes: [:empty, {:circle, 1.0}],
+      tags: %{"a" => 1, "b" => 2},
+      parent: nil,
+      size: {640, 480},
+    }
+    assert RustlerTest.serde_roundtrip(drawing) == drawing
+    assert RustlerTest.serde_roundtrip(%{drawing | parent: "other"}) == %{drawing | parent: "other"}
+
+    # Atom keys are read as strings.
+    assert RustlerTest.serde_roundtrip(%{drawing | tags: %{a: 1}}) == %{drawing | tags: %{"a" => 1}}
+  end
+
+  test "deserialization errors" do
+    assert {:error, "expected map"} = RustlerTest.serde_shape_area({:polygon, []})
+    assert {:error, "expected %RustlerTest.Point{}"} =
+      RustlerTest.serde_shape_area({:rect, %{x: 1, y: 1}, %Point{x: 3, y: 4}})
+    assert {:error, _} = RustlerTest.serde_shape_area(:square)
+    assert {:error, "expected proper list"} =
+      RustlerTest.serde_shape_area({:polygon, %{points: [%Point{x: 1, y: 2} | :tail]}})
+
+    assert_raise ArgumentError, "expected u32", fn ->
+      RustlerTest.serde_roundtrip(%{name: "a", shapes: [], tags: %{"a" => -1}, parent: nil, size: {1, 1}})
+    end
+  end
+end
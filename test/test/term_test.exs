@@ -0,0 +1,64 @@
+defmodule RustlerTest.TermTest do
+  use ExUnit.Case, async: true
+
+  test "terms compare in term order" do
+    assert RustlerTest.term_cmp(1, 2) == :lt
+    assert RustlerTest.term_cmp(:a, 1) == :gt
+    assert RustlerTest.term_cmp({1, "a"}, {1, "a"}) == :eq
+    assert RustlerTest.term_cmp([1, 2], [1]) == :gt
+    assert RustlerTest.term_cmp(1, 1.0) != :eq
+  end
+
+  test "terms can be deduplicated with a hash set" do
+    assert RustlerTest.unique_terms([3, :a, 3, {1}, :a, "b", {1}]) == [3, :a, {1}, "b"]
+    assert length(RustlerTest.unique_terms([1, 1.0])) == 2
+  end
+
+  defp elixir(term, max_depth \\ nil, max_items \\ nil) do
+    RustlerTest.term_format(term, :elixir, max_depth, max_items)
+  end
+
+  defp erlang(term, max_depth \\ nil, max_items \\ nil) do
+    RustlerTest.term_format(term, :erlang, max_depth, max_items)
+  end
+
+  test "terms format like inspect in Elixir" do
+    for term <- [
+      :atom, :"with space", :Foo, :question?, true, nil, RustlerTest.TermTest,
+      0, -12, 12345678901234567890123, -12345678901234567890123, 1.5, 1.0e20, -0.5,
+      "string", "quote\"d\n", <<0, 255>>, <<1, 2::3>>,
+      [], [1, [2, :b]], [1 | 2], {}, {:ok, "a"},
+      %{}, %{a: [1, 2]}, %{"b" => 1}, %RustlerTest.Point{x: 1, y: 2},
+    ] do
+      assert elixir(term) == inspect(term, charlists: :as_lists)
+    end
+  end
+
+  test "terms format in Erlang syntax" do
+    assert erlang(%{a: [1, 2]}) == "\#{a => [1,2]}"
+    assert erlang({:ok, "a", <<0, 1>>}) == "{ok,<<\"a\">>,<<0,1>>}"
+    assert erlang([:"Foo", :"end", :"a b", :a@b | :tail]) == "['Foo','end','a b',a@b|tail]"
+    assert erlang(<<1, 2::3>>) == "<<1,2:3>>"
+    assert erlang(self()) == "<...>"
+  end
+
+  test "formatting with limits" do
+    assert elixir([1, [2, [3, [4]]]], 2) == "[1, [2, ...]]"
+    assert elixir(Enum.to_list(1..5), nil, 3) == "[1, 2, 3, ...]"
+    assert elixir({1, 2, 3}, nil, 2) == "{1, 2, ...}"
+    assert elixir("abcdef", nil, 3) == "\"abc\" <> ..."
+    assert erlang(%{a: Enum.to_list(1..5)}, nil, 3) == "\#{a => [1,2,3,...]}"
+    assert RustlerTest.term_debug(Enum.to_list(1..100)) == inspect(Enum.to_list(1..100))
+  end
+
+  test "terms built with the term macro" do
+    assert RustlerTest.term_macro(3, [:x]) == {:ok, %{
+      :count => 3,
+      :items => [:x],
+      "double" => 6,
+      :status => :ok,
+      :nested => [:a, {:b, [1, 2.5, "three"]}, %{}, {}, []],
+      3 => :n,
+    }}
+  end
+end